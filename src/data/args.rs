@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+
+use crate::simulation::difficulty::Difficulty;
+use crate::simulation::maze::{MazeDensity, MazeStyle};
+
+/// Command line flags, parsed once at startup. Each one given takes
+/// precedence over the matching config field.
+#[derive(Resource, Default)]
+pub struct LaunchArgs {
+    // `--config <path>`
+    pub config_path: Option<String>,
+    // `--seed <number>`
+    pub seed: Option<u64>,
+    // `--difficulty <name>`
+    pub difficulty: Option<Difficulty>,
+    // `--level <path>`, relative to the assets folder
+    pub level_path: Option<String>,
+    // `--maze <style>`
+    pub maze_style: Option<MazeStyle>,
+    // `--density <density>`
+    pub maze_density: Option<MazeDensity>,
+    // `--wrap`
+    pub wrap: bool,
+    // Problems met while parsing, logged once the app is running
+    pub warnings: Vec<String>,
+}
+
+impl LaunchArgs {
+    /// Parses the arguments following the program name
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        let mut launch_args = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(flag) = args.next() {
+            match flag.as_str() {
+                "--wrap" => launch_args.wrap = true,
+                "--config" | "--seed" | "--difficulty" | "--level" | "--maze" | "--density" => {
+                    match args.next() {
+                        Some(value) => launch_args.set(&flag, value),
+                        None => launch_args
+                            .warnings
+                            .push(format!("Missing value after {flag}")),
+                    }
+                }
+                _ => launch_args
+                    .warnings
+                    .push(format!("Unknown argument \"{flag}\", ignored")),
+            }
+        }
+
+        launch_args
+    }
+
+    fn set(&mut self, flag: &str, value: String) {
+        match flag {
+            "--config" => self.config_path = Some(value),
+            "--level" => self.level_path = Some(value),
+            "--seed" => match value.parse() {
+                Ok(seed) => self.seed = Some(seed),
+                Err(_) => self
+                    .warnings
+                    .push(format!("Invalid seed \"{value}\", using a random one")),
+            },
+            "--difficulty" => match Difficulty::from_name(&value) {
+                Some(difficulty) => self.difficulty = Some(difficulty),
+                None => self.warnings.push(format!(
+                    "Invalid difficulty \"{value}\", using the config one"
+                )),
+            },
+            "--maze" => match MazeStyle::from_name(&value) {
+                Some(style) => self.maze_style = Some(style),
+                None => self.warnings.push(format!(
+                    "Invalid maze style \"{value}\", using the config one"
+                )),
+            },
+            "--density" => match MazeDensity::from_name(&value) {
+                Some(density) => self.maze_density = Some(density),
+                None => self.warnings.push(format!(
+                    "Invalid maze density \"{value}\", using the config one"
+                )),
+            },
+            _ => unreachable!("{flag} takes no value"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> LaunchArgs {
+        LaunchArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parse_reads_every_flag() {
+        let launch_args = parse(&[
+            "--config",
+            "other.ron",
+            "--seed",
+            "42",
+            "--level",
+            "levels/cross.level",
+            "--wrap",
+        ]);
+
+        assert_eq!(launch_args.config_path.as_deref(), Some("other.ron"));
+        assert_eq!(launch_args.seed, Some(42));
+        assert_eq!(
+            launch_args.level_path.as_deref(),
+            Some("levels/cross.level")
+        );
+        assert!(launch_args.wrap);
+        assert!(launch_args.warnings.is_empty());
+    }
+
+    #[test]
+    fn parse_warns_about_unknown_flags_and_bad_values() {
+        let launch_args = parse(&["--wall", "--seed", "abc", "--maze"]);
+
+        assert_eq!(launch_args.seed, None);
+        assert!(!launch_args.wrap);
+        assert_eq!(launch_args.warnings.len(), 3);
+    }
+}
@@ -1,8 +1,17 @@
 use bevy::prelude::Color;
 
+// Default values of `GameConfig`, see `config.rs`
+
 // Walls
 pub const WALL_COLOR: Color = Color::srgb_u8(0x42, 0xab, 0x49); // #42ab49
 
+// Portals, one color per pair, reused when there are more pairs
+pub const PORTAL_COLORS: [Color; 3] = [
+    Color::srgb_u8(0x7b, 0x3f, 0xe4), // #7b3fe4
+    Color::srgb_u8(0xff, 0x8c, 0x1a), // #ff8c1a
+    Color::srgb_u8(0x1a, 0x9f, 0xff), // #1a9fff
+];
+
 // Background
 pub const BACKGROUND_COLOR: Color = Color::srgb_u8(0x89, 0xe1, 0x86); // #89e186
 
@@ -11,3 +20,15 @@ pub const SCOREBOARD_TEXT_COLOR: Color = Color::srgb_u8(0xff, 0xff, 0xff); // #f
 
 // Credits
 pub const CREDITS_FONT_COLOR: Color = Color::srgb_u8(0xff, 0xff, 0xff); // #8080ff
+
+// Food
+pub const FOOD_COUNTDOWN_COLOR: Color = Color::srgb_u8(0xff, 0xff, 0xff); // #ffffff
+
+// Enemies
+pub const HAZARD_COLOR: Color = Color::srgb_u8(0xd7, 0x26, 0x3d); // #d7263d
+
+// Overlay screens
+pub const OVERLAY_BACKGROUND_COLOR: Color = Color::srgba_u8(0x00, 0x00, 0x00, 0x99); // #00000099
+pub const OVERLAY_TEXT_COLOR: Color = Color::srgb_u8(0xff, 0xff, 0xff); // #ffffff
+pub const OVERLAY_BUTTON_COLOR: Color = Color::srgba_u8(0xff, 0xff, 0xff, 0x26); // #ffffff26
+pub const OVERLAY_SELECTED_BUTTON_COLOR: Color = Color::srgb_u8(0x42, 0xab, 0x49); // #42ab49
@@ -0,0 +1,574 @@
+use std::{fmt, fs, io, path::Path};
+
+use bevy::prelude::*;
+use serde::{de, Deserialize, Deserializer};
+
+use crate::simulation::difficulty::Difficulty;
+use crate::simulation::food::FoodKind;
+use crate::simulation::maze::{MazeDensity, MazeStyle};
+
+use super::{colors::*, constants::*};
+
+/// Settings read from a RON file at startup. Fields missing from the file
+/// keep the defaults from `constants.rs` and `colors.rs`.
+#[derive(Resource, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    pub seed: Option<u64>,
+    pub window: WindowConfig,
+    pub arena: ArenaConfig,
+    pub snake: SnakeConfig,
+    pub maze: MazeConfig,
+    pub food: FoodConfig,
+    pub hazards: HazardConfig,
+    pub time_attack: TimeAttackConfig,
+    pub background_color: HexColor,
+    pub scoreboard: ScoreboardConfig,
+    pub credits: CreditsConfig,
+    pub overlay: OverlayConfig,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowConfig {
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArenaConfig {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+    pub width_offset: f32,
+    pub height_offset: f32,
+    pub wall_thickness: f32,
+    pub wall_dash_length: f32,
+    pub wall_color: HexColor,
+    // Portal pairs take these colors in turn
+    pub portal_colors: Vec<HexColor>,
+    // Relative to the snake size
+    pub portal_size: f32,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct SnakeConfig {
+    pub size: f32,
+    // Start speed, speed-up per apple and max speed
+    pub difficulty: Difficulty,
+    pub max_queued_turns: usize,
+    pub start_cell: (i32, i32),
+    // Replaced by `difficulty`, still accepted in older config files and
+    // only read to warn that it is ignored
+    pub seconds_per_movement: Option<f32>,
+}
+
+/// Random walls added to the endless mode arena
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct MazeConfig {
+    // No random walls when `None`
+    pub style: Option<MazeStyle>,
+    pub density: MazeDensity,
+    // Free cells kept in front of the snake head
+    pub safe_corridor: usize,
+}
+
+/// Sprite, sound, spawn weight and lifetime of each food kind
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct FoodConfig {
+    // Food on the board at once
+    pub count: usize,
+    // Timed food blinks during its last seconds
+    pub blink_seconds: f32,
+    pub countdown_color: HexColor,
+    // Seconds a pepper or an ice cube changes the snake speed
+    pub speed_effect_seconds: f32,
+    pub apple: FoodKindConfig,
+    pub golden: FoodKindConfig,
+    pub rotten: FoodKindConfig,
+    pub pepper: FoodKindConfig,
+    pub ice: FoodKindConfig,
+}
+
+/// A food kind written in the config file must list every field
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct FoodKindConfig {
+    pub sprite: String,
+    pub sound: String,
+    // Relative chance of being the next food, zero to never spawn
+    pub weight: u32,
+    // Seconds before the food vanishes, `None` to stay. An apple moves to
+    // another cell, other kinds make room for an apple.
+    pub lifetime: Option<f32>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct HazardConfig {
+    pub seconds_per_movement: f32,
+    // Points lost for each tail segment an enemy cuts off
+    pub score_penalty: usize,
+    pub color: HexColor,
+    // Relative to the snake size
+    pub size: f32,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimeAttackConfig {
+    // Length of the clock at the start of a run
+    pub seconds: f32,
+    // Added to the clock for each apple eaten, golden ones included
+    pub apple_bonus_seconds: f32,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScoreboardConfig {
+    pub font_size: f32,
+    pub text_color: HexColor,
+    pub left_padding: f32,
+    pub top_padding: f32,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct CreditsConfig {
+    pub font_size: f32,
+    pub font_color: HexColor,
+    pub right_padding: f32,
+    pub bottom_padding: f32,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct OverlayConfig {
+    pub title_font_size: f32,
+    pub hint_font_size: f32,
+    pub row_gap: f32,
+    pub background_color: HexColor,
+    pub text_color: HexColor,
+    // Menu entries, the one picked with the keyboard or the mouse stands out
+    pub button_color: HexColor,
+    pub selected_button_color: HexColor,
+    pub button_width: f32,
+    pub button_padding: f32,
+    // Seconds counted down before play resumes after a pause
+    pub resume_countdown_seconds: f32,
+}
+
+/// A color written as a hex string, such as "#42ab49", in the config file
+#[derive(Clone, Copy, Deref)]
+pub struct HexColor(pub Color);
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Invalid(String),
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            window: WindowConfig::default(),
+            arena: ArenaConfig::default(),
+            snake: SnakeConfig::default(),
+            maze: MazeConfig::default(),
+            food: FoodConfig::default(),
+            hazards: HazardConfig::default(),
+            time_attack: TimeAttackConfig::default(),
+            background_color: HexColor(BACKGROUND_COLOR),
+            scoreboard: ScoreboardConfig::default(),
+            credits: CreditsConfig::default(),
+            overlay: OverlayConfig::default(),
+        }
+    }
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            width: WIDTH_SIZE_WINDOW,
+            height: HEIGHT_SIZE_WINDOW,
+        }
+    }
+}
+
+impl Default for ArenaConfig {
+    fn default() -> Self {
+        Self {
+            left: WALL_LEFT,
+            right: WALL_RIGHT,
+            top: WALL_TOP,
+            bottom: WALL_BOTTOM,
+            width_offset: WALL_WIDTH_OFFSET,
+            height_offset: WALL_HEIGHT_OFFSET,
+            wall_thickness: WALL_THICKNESS,
+            wall_dash_length: WALL_DASH_LENGTH,
+            wall_color: HexColor(WALL_COLOR),
+            portal_colors: PORTAL_COLORS.map(HexColor).to_vec(),
+            portal_size: PORTAL_SIZE,
+        }
+    }
+}
+
+impl Default for SnakeConfig {
+    fn default() -> Self {
+        Self {
+            size: SNAKE_SIZE,
+            difficulty: Difficulty::Normal,
+            max_queued_turns: SNAKE_MAX_QUEUED_TURNS,
+            start_cell: (SNAKE_START_CELL_X, SNAKE_START_CELL_Y),
+            seconds_per_movement: None,
+        }
+    }
+}
+
+impl Default for MazeConfig {
+    fn default() -> Self {
+        Self {
+            style: None,
+            density: MazeDensity::Medium,
+            safe_corridor: MAZE_SAFE_CORRIDOR,
+        }
+    }
+}
+
+impl Default for FoodConfig {
+    fn default() -> Self {
+        let kind = |sprite: &str, sound: &str, weight, lifetime| FoodKindConfig {
+            sprite: sprite.to_string(),
+            sound: sound.to_string(),
+            weight,
+            lifetime,
+        };
+
+        Self {
+            count: FOOD_COUNT,
+            blink_seconds: FOOD_BLINK_SECONDS,
+            countdown_color: HexColor(FOOD_COUNTDOWN_COLOR),
+            speed_effect_seconds: FOOD_SPEED_EFFECT_SECONDS,
+            apple: kind("sprites/apple.png", "audio/coin_000.ogg", 10, None),
+            golden: kind(
+                "sprites/food-golden.png",
+                "audio/coin_000.ogg",
+                2,
+                Some(6.0),
+            ),
+            rotten: kind(
+                "sprites/food-rotten.png",
+                "audio/pluck_002.ogg",
+                2,
+                Some(8.0),
+            ),
+            pepper: kind(
+                "sprites/food-pepper.png",
+                "audio/pluck_001.ogg",
+                1,
+                Some(6.0),
+            ),
+            ice: kind("sprites/food-ice.png", "audio/pluck_001.ogg", 1, Some(6.0)),
+        }
+    }
+}
+
+impl FoodConfig {
+    pub fn kind(&self, kind: FoodKind) -> &FoodKindConfig {
+        match kind {
+            FoodKind::Apple => &self.apple,
+            FoodKind::Golden => &self.golden,
+            FoodKind::Rotten => &self.rotten,
+            FoodKind::Pepper => &self.pepper,
+            FoodKind::Ice => &self.ice,
+        }
+    }
+}
+
+impl Default for HazardConfig {
+    fn default() -> Self {
+        Self {
+            seconds_per_movement: HAZARD_SECONDS_PER_MOVEMENT,
+            score_penalty: HAZARD_SCORE_PENALTY,
+            color: HexColor(HAZARD_COLOR),
+            size: HAZARD_SIZE,
+        }
+    }
+}
+
+impl Default for TimeAttackConfig {
+    fn default() -> Self {
+        Self {
+            seconds: TIME_ATTACK_SECONDS,
+            apple_bonus_seconds: TIME_ATTACK_APPLE_BONUS_SECONDS,
+        }
+    }
+}
+
+impl Default for ScoreboardConfig {
+    fn default() -> Self {
+        Self {
+            font_size: SCOREBOARD_FONT_SIZE,
+            text_color: HexColor(SCOREBOARD_TEXT_COLOR),
+            left_padding: SCOREBOARD_TEXT_LEFT_PADDING,
+            top_padding: SCOREBOARD_TEXT_TOP_PADDING,
+        }
+    }
+}
+
+impl Default for CreditsConfig {
+    fn default() -> Self {
+        Self {
+            font_size: CREDITS_FONT_SIZE,
+            font_color: HexColor(CREDITS_FONT_COLOR),
+            right_padding: CREDITS_TEXT_RIGHT_PADDING,
+            bottom_padding: CREDITS_TEXT_BOTTOM_PADDING,
+        }
+    }
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            title_font_size: OVERLAY_TITLE_FONT_SIZE,
+            hint_font_size: OVERLAY_HINT_FONT_SIZE,
+            row_gap: OVERLAY_ROW_GAP,
+            background_color: HexColor(OVERLAY_BACKGROUND_COLOR),
+            text_color: HexColor(OVERLAY_TEXT_COLOR),
+            button_color: HexColor(OVERLAY_BUTTON_COLOR),
+            selected_button_color: HexColor(OVERLAY_SELECTED_BUTTON_COLOR),
+            button_width: OVERLAY_BUTTON_WIDTH,
+            button_padding: OVERLAY_BUTTON_PADDING,
+            resume_countdown_seconds: RESUME_COUNTDOWN_SECONDS,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for HexColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+
+        Srgba::hex(&hex)
+            .map(|color| HexColor(color.into()))
+            .map_err(|err| de::Error::custom(format!("invalid color \"{hex}\": {err}")))
+    }
+}
+
+impl GameConfig {
+    /// Reads and validates the config file. A missing file falls back to
+    /// the default config.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let config: GameConfig = match fs::read_to_string(path) {
+            Ok(text) => ron::from_str(&text).map_err(ConfigError::Parse)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => GameConfig::default(),
+            Err(err) => return Err(ConfigError::Io(err)),
+        };
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |message: String| Err(ConfigError::Invalid(message));
+
+        if self.window.width <= 0.0 || self.window.height <= 0.0 {
+            return invalid("the window size must be positive".to_string());
+        }
+        if self.snake.size <= 0.0 {
+            return invalid("the snake size must be positive".to_string());
+        }
+        if self.snake.max_queued_turns == 0 {
+            return invalid("at least one queued turn must be allowed".to_string());
+        }
+        if self.arena.right <= self.arena.left || self.arena.top <= self.arena.bottom {
+            return invalid(
+                "the arena right and top walls must be past the left and bottom ones".to_string(),
+            );
+        }
+        if self.arena.wall_thickness < 0.0 || self.arena.wall_dash_length <= 0.0 {
+            return invalid("the wall thickness and dash length must be positive".to_string());
+        }
+
+        if self.food.count == 0 {
+            return invalid("at least one food must be on the board".to_string());
+        }
+        if FoodKind::ALL
+            .iter()
+            .all(|&kind| self.food.kind(kind).weight == 0)
+        {
+            return invalid("at least one food kind must have a spawn weight".to_string());
+        }
+        if FoodKind::ALL.iter().any(|&kind| {
+            self.food
+                .kind(kind)
+                .lifetime
+                .is_some_and(|lifetime| lifetime <= 0.0)
+        }) {
+            return invalid("the food lifetimes must be positive".to_string());
+        }
+        if self.food.blink_seconds < 0.0 {
+            return invalid("the food blink seconds must not be negative".to_string());
+        }
+        if self.food.speed_effect_seconds <= 0.0 {
+            return invalid("the food speed effect seconds must be positive".to_string());
+        }
+
+        if self.hazards.seconds_per_movement <= 0.0 {
+            return invalid("the enemy seconds per movement must be positive".to_string());
+        }
+        if self.time_attack.seconds <= 0.0 {
+            return invalid("the time attack seconds must be positive".to_string());
+        }
+        if self.time_attack.apple_bonus_seconds < 0.0 {
+            return invalid("the time attack apple bonus must not be negative".to_string());
+        }
+        if self.arena.portal_colors.is_empty() {
+            return invalid("at least one portal color is needed".to_string());
+        }
+        if self.arena.portal_size <= 0.0 || self.arena.portal_size > 1.0 {
+            return invalid("the portal size must be between 0 and 1".to_string());
+        }
+        if self.hazards.size <= 0.0 || self.hazards.size > 1.0 {
+            return invalid("the enemy size must be between 0 and 1".to_string());
+        }
+        if self.overlay.button_width <= 0.0 || self.overlay.button_padding < 0.0 {
+            return invalid(
+                "the overlay button width must be positive and its padding not negative"
+                    .to_string(),
+            );
+        }
+        if self.overlay.resume_countdown_seconds < 0.0 {
+            return invalid("the resume countdown seconds must not be negative".to_string());
+        }
+
+        // The space between the walls must hold a whole number of cells
+        let inner_width = self.arena.right - self.arena.left - self.arena.wall_thickness;
+        let inner_height = self.arena.top - self.arena.bottom - self.arena.wall_thickness;
+
+        for (side, inner_size) in [("width", inner_width), ("height", inner_height)] {
+            let cells = inner_size / self.snake.size;
+
+            if cells < 2.0 || (cells - cells.round()).abs() > 1e-3 {
+                return invalid(format!(
+                    "the arena {side} between the walls ({inner_size}) must be a multiple of \
+                     the snake size ({}) of at least two cells",
+                    self.snake.size
+                ));
+            }
+        }
+
+        // The snake starts with its tail one cell behind the head
+        let (start_x, start_y) = self.snake.start_cell;
+        if !(1..self.cell_columns()).contains(&start_x) || !(0..self.cell_rows()).contains(&start_y)
+        {
+            return invalid(format!(
+                "the snake start cell ({start_x}, {start_y}) must leave room for its tail inside \
+                 the {}x{} grid",
+                self.cell_columns(),
+                self.cell_rows()
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn cell_columns(&self) -> i32 {
+        ((self.arena.right - self.arena.left - self.arena.wall_thickness) / self.snake.size).round()
+            as i32
+    }
+
+    pub fn cell_rows(&self) -> i32 {
+        ((self.arena.top - self.arena.bottom - self.arena.wall_thickness) / self.snake.size).round()
+            as i32
+    }
+
+    /// Center of the space between the walls
+    pub fn arena_center(&self) -> Vec2 {
+        Vec2::new(
+            (self.arena.left + self.arena.right) / 2.0 + self.arena.width_offset,
+            (self.arena.top + self.arena.bottom) / 2.0 + self.arena.height_offset,
+        )
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "could not read the config file: {err}"),
+            ConfigError::Parse(err) => write!(f, "could not parse the config file: {err}"),
+            ConfigError::Invalid(message) => write!(f, "invalid config: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_invalid(config: &GameConfig) {
+        assert!(matches!(config.validate(), Err(ConfigError::Invalid(_))));
+    }
+
+    #[test]
+    fn validate_accepts_the_defaults_and_the_shipped_config() {
+        assert!(GameConfig::default().validate().is_ok());
+
+        let config: GameConfig = ron::from_str(include_str!("../../assets/config.ron")).unwrap();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_an_arena_that_does_not_fit_the_cells() {
+        let mut config = GameConfig::default();
+        config.snake.size += 1.0;
+        assert_invalid(&config);
+
+        let mut config = GameConfig::default();
+        config.arena.right = config.arena.left;
+        assert_invalid(&config);
+
+        let mut config = GameConfig::default();
+        config.snake.start_cell = (0, 0);
+        assert_invalid(&config);
+    }
+
+    #[test]
+    fn validate_rejects_food_that_can_never_spawn() {
+        let mut config = GameConfig::default();
+        config.food.count = 0;
+        assert_invalid(&config);
+
+        let mut config = GameConfig::default();
+        for kind in [
+            &mut config.food.apple,
+            &mut config.food.golden,
+            &mut config.food.rotten,
+            &mut config.food.pepper,
+            &mut config.food.ice,
+        ] {
+            kind.weight = 0;
+        }
+        assert_invalid(&config);
+
+        let mut config = GameConfig::default();
+        config.food.golden.lifetime = Some(0.0);
+        assert_invalid(&config);
+    }
+
+    #[test]
+    fn deserialize_accepts_the_old_snake_speed_key() {
+        let config: GameConfig = ron::from_str("(snake: (seconds_per_movement: 0.1))").unwrap();
+
+        assert_eq!(config.snake.seconds_per_movement, Some(0.1));
+        assert!(config.validate().is_ok());
+    }
+}
@@ -1,26 +1,12 @@
-use bevy::ui::Val;
+// Default values of `GameConfig`, see `config.rs`
 
 // Snake
-// ! IMPORTANT:
-// ! This values should be multiples of
-// ! (abs(WALL_TOP) + abs(WALL_BOTTOM) - WALL_THICKNESS) and (abs(WALL_LEFT) + abs(WALL_RIGHT) - WALL_THICKNESS)
-pub const SNAKE_SPEED: f32 = 40.0;
 pub const SNAKE_SIZE: f32 = 40.0;
-pub const APPLE_SIZE: f32 = SNAKE_SIZE;
 
-pub const SNAKE_SECONDS_PER_MOVEMENT: f32 = 0.2;
+pub const SNAKE_MAX_QUEUED_TURNS: usize = 3;
 
-pub const HALF_SNAKE_SIZE: f32 = SNAKE_SIZE / 2.0;
-pub const HALF_APPLE_SIZE: f32 = APPLE_SIZE / 2.0;
-
-pub const SNAKE_Y_MAX: f32 =
-    WALL_TOP + WALL_HEIGHT_OFFSET - WALL_THICKNESS - HALF_SNAKE_SIZE + 10.0;
-pub const SNAKE_Y_MIN: f32 =
-    WALL_BOTTOM + WALL_HEIGHT_OFFSET + WALL_THICKNESS + HALF_SNAKE_SIZE - 10.0;
-pub const SNAKE_X_MAX: f32 =
-    WALL_RIGHT + WALL_WIDTH_OFFSET - WALL_THICKNESS - HALF_SNAKE_SIZE + 10.0;
-pub const SNAKE_X_MIN: f32 =
-    WALL_LEFT + WALL_WIDTH_OFFSET + WALL_THICKNESS + HALF_SNAKE_SIZE - 10.0;
+pub const SNAKE_START_CELL_X: i32 = 10;
+pub const SNAKE_START_CELL_Y: i32 = 7;
 
 // Window
 pub const WIDTH_SIZE_WINDOW: f32 = 800.0;
@@ -31,31 +17,67 @@ pub const WALL_HEIGHT_OFFSET: f32 = -40.0;
 pub const WALL_WIDTH_OFFSET: f32 = 0.0;
 
 pub const WALL_THICKNESS: f32 = 20.0;
+pub const WALL_DASH_LENGTH: f32 = 20.0;
 
 pub const WALL_LEFT: f32 = -450.0;
 pub const WALL_RIGHT: f32 = 450.0;
 pub const WALL_TOP: f32 = 270.0;
 pub const WALL_BOTTOM: f32 = -270.0;
 
-// Cells
-pub const CELL_X_SIZE: f32 =
-    ((ABS_WALL_RIGHT + ABS_WALL_LEFT - WALL_THICKNESS) / SNAKE_SIZE) as i32 as f32;
-pub const CELL_Y_SIZE: f32 =
-    ((ABS_WALL_TOP + ABS_WALL_BOTTOM - WALL_THICKNESS) / SNAKE_SIZE) as i32 as f32;
-
-// Aux
-pub const ABS_WALL_BOTTOM: f32 = WALL_BOTTOM * (1.0 - 2.0 * ((WALL_BOTTOM < 0.0) as i32) as f32);
-pub const ABS_WALL_TOP: f32 = WALL_TOP * (1.0 - 2.0 * ((WALL_TOP < 0.0) as i32) as f32);
-
-pub const ABS_WALL_LEFT: f32 = WALL_LEFT * (1.0 - 2.0 * ((WALL_LEFT < 0.0) as i32) as f32);
-pub const ABS_WALL_RIGHT: f32 = WALL_RIGHT * (1.0 - 2.0 * ((WALL_RIGHT < 0.0) as i32) as f32);
+// Portals, size relative to the cell size
+pub const PORTAL_SIZE: f32 = 0.8;
 
 // Scoreboard
 pub const SCOREBOARD_FONT_SIZE: f32 = 33.0;
-pub const SCOREBOARD_TEXT_LEFT_PADDING: Val = Val::Px(180.0);
-pub const SCOREBOARD_TEXT_TOP_PADDING: Val = Val::Px(40.0);
+pub const SCOREBOARD_TEXT_LEFT_PADDING: f32 = 180.0;
+pub const SCOREBOARD_TEXT_TOP_PADDING: f32 = 40.0;
 
 // Credits
 pub const CREDITS_FONT_SIZE: f32 = 18.0;
-pub const CREDITS_TEXT_RIGHT_PADDING: Val = Val::Px(180.0);
-pub const CREDITS_TEXT_BOTTOM_PADDING: Val = Val::Px(20.0);
+pub const CREDITS_TEXT_RIGHT_PADDING: f32 = 180.0;
+pub const CREDITS_TEXT_BOTTOM_PADDING: f32 = 20.0;
+
+// Overlay screens
+pub const OVERLAY_TITLE_FONT_SIZE: f32 = 60.0;
+pub const OVERLAY_HINT_FONT_SIZE: f32 = 24.0;
+pub const OVERLAY_ROW_GAP: f32 = 20.0;
+pub const OVERLAY_BUTTON_WIDTH: f32 = 320.0;
+pub const OVERLAY_BUTTON_PADDING: f32 = 10.0;
+// Seconds counted down before play resumes after a pause
+pub const RESUME_COUNTDOWN_SECONDS: f32 = 3.0;
+
+// Config file
+pub const DEFAULT_CONFIG_PATH: &str = "assets/config.ron";
+
+// Maze generator
+pub const MAZE_SAFE_CORRIDOR: usize = 4;
+
+// Food
+pub const FOOD_COUNT: usize = 1;
+pub const FOOD_BLINK_SECONDS: f32 = 2.0;
+pub const FOOD_BLINK_INTERVAL: f32 = 0.15;
+// Height of the countdown bar under timed food, relative to the cell size
+pub const FOOD_COUNTDOWN_HEIGHT: f32 = 0.1;
+// Seconds a pepper or an ice cube changes the snake speed
+pub const FOOD_SPEED_EFFECT_SECONDS: f32 = 5.0;
+
+// Campaign
+pub const CAMPAIGN_PATH: &str = "assets/campaign.ron";
+pub const CAMPAIGN_SAVE_PATH: &str = "campaign_save.ron";
+
+// High scores
+pub const HIGH_SCORES_PATH: &str = "high_scores.ron";
+
+// Time attack
+pub const TIME_ATTACK_SECONDS: f32 = 90.0;
+pub const TIME_ATTACK_APPLE_BONUS_SECONDS: f32 = 3.0;
+
+// Level editor
+pub const EDITOR_LEVEL_PATH: &str = "assets/levels/editor.level.ron";
+pub const EDITOR_HUD_FONT_SIZE: f32 = 16.0;
+
+// Enemies
+pub const HAZARD_SECONDS_PER_MOVEMENT: f32 = 0.4;
+pub const HAZARD_SCORE_PENALTY: usize = 1;
+// Size relative to the cell size
+pub const HAZARD_SIZE: f32 = 0.7;
@@ -1,2 +1,4 @@
+pub mod args;
 pub mod colors;
+pub mod config;
 pub mod constants;
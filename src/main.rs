@@ -1,34 +1,79 @@
 mod data;
 mod plugins;
+mod simulation;
 mod utils;
 
-use data::constants::*;
+use data::args::LaunchArgs;
+use data::config::GameConfig;
+use data::constants::DEFAULT_CONFIG_PATH;
 use plugins::background::background_plugin;
 use plugins::camera::camera_plugin;
+use plugins::campaign::campaign_plugin;
+use plugins::editor::editor_plugin;
+use plugins::food::food_plugin;
+use plugins::game_state::game_state_plugin;
+use plugins::high_scores::high_scores_plugin;
+use plugins::level::level_plugin;
+use plugins::menu::menu_plugin;
+use plugins::pause::pause_plugin;
+use plugins::portal::portal_plugin;
+use plugins::rng::rng_plugin;
 use plugins::score::score_plugin;
 use plugins::snake::snake_plugin;
+use plugins::time_attack::time_attack_plugin;
 use plugins::wall::wall_plugin;
 
 use bevy::prelude::*;
 
 fn main() {
+    let launch_args = LaunchArgs::parse(std::env::args().skip(1));
+    let config_path = launch_args
+        .config_path
+        .clone()
+        .unwrap_or_else(|| DEFAULT_CONFIG_PATH.to_string());
+    let config = GameConfig::load(&config_path).unwrap_or_else(|err| {
+        eprintln!("{config_path}: {err}");
+        std::process::exit(1);
+    });
+
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 resizable: false,
                 resize_constraints: WindowResizeConstraints {
-                    min_width: WIDTH_SIZE_WINDOW,
-                    min_height: HEIGHT_SIZE_WINDOW,
+                    min_width: config.window.width,
+                    min_height: config.window.height,
                     ..default()
                 },
                 ..default()
             }),
             ..default()
         }))
+        .insert_resource(config)
+        .insert_resource(launch_args)
+        .add_systems(Startup, warn_about_args)
+        .add_plugins(rng_plugin)
+        .add_plugins(level_plugin)
+        .add_plugins(game_state_plugin)
+        .add_plugins(campaign_plugin)
         .add_plugins(background_plugin)
         .add_plugins(wall_plugin)
+        .add_plugins(portal_plugin)
         .add_plugins(camera_plugin)
         .add_plugins(snake_plugin)
+        .add_plugins(food_plugin)
         .add_plugins(score_plugin)
+        .add_plugins(time_attack_plugin)
+        .add_plugins(editor_plugin)
+        .add_plugins(high_scores_plugin)
+        .add_plugins(menu_plugin)
+        .add_plugins(pause_plugin)
         .run();
 }
+
+/// Logs what went wrong while parsing the command line
+fn warn_about_args(launch_args: Res<LaunchArgs>) {
+    for warning in &launch_args.warnings {
+        warn!("{warning}");
+    }
+}
@@ -0,0 +1,221 @@
+use std::fs;
+
+use bevy::prelude::*;
+
+use crate::data::config::GameConfig;
+use crate::data::constants::{CAMPAIGN_PATH, CAMPAIGN_SAVE_PATH};
+use crate::simulation::campaign::{CampaignFile, CampaignSave, LevelGoal};
+
+use super::game_state::{spawn_screen, GameMode, GameState};
+use super::level::{LevelAsset, LevelHandle};
+use super::score::{ApplesEaten, ElapsedTime};
+
+struct CampaignLevel {
+    name: String,
+    goal: LevelGoal,
+    handle: Handle<LevelAsset>,
+}
+
+/// Levels of the campaign and how far the player got, missing when the
+/// campaign file could not be loaded
+#[derive(Resource)]
+pub struct Campaign {
+    levels: Vec<CampaignLevel>,
+    current: usize,
+    // Number of levels the player can pick, saved between runs
+    unlocked: usize,
+    // The player just cleared the level before `current`
+    cleared: bool,
+}
+
+#[derive(Component)]
+struct CampaignScreen;
+
+pub fn campaign_plugin(app: &mut App) {
+    app.add_systems(Startup, load_campaign)
+        .add_systems(
+            OnTransition {
+                exited: GameState::Menu,
+                entered: GameState::LevelTransition,
+            },
+            start_campaign,
+        )
+        .add_systems(OnEnter(GameState::LevelTransition), use_campaign_level)
+        .add_systems(
+            Update,
+            (pick_level, refresh_campaign_screen)
+                .chain()
+                .run_if(in_state(GameState::LevelTransition)),
+        )
+        .add_systems(
+            Update,
+            check_level_goal
+                .run_if(in_state(GameState::Playing).and(resource_equals(GameMode::Campaign))),
+        );
+}
+
+fn load_campaign(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let file = match fs::read_to_string(CAMPAIGN_PATH)
+        .map_err(|err| err.to_string())
+        .and_then(|text| CampaignFile::from_ron(&text).map_err(|err| err.to_string()))
+    {
+        Ok(file) => file,
+        Err(err) => {
+            warn!("could not load the campaign from {CAMPAIGN_PATH}, it is disabled: {err}");
+            return;
+        }
+    };
+
+    let levels: Vec<CampaignLevel> = file
+        .levels
+        .into_iter()
+        .map(|entry| CampaignLevel {
+            handle: asset_server.load(entry.path),
+            name: entry.name,
+            goal: entry.goal,
+        })
+        .collect();
+
+    // A missing or broken save starts the campaign over
+    let unlocked = fs::read_to_string(CAMPAIGN_SAVE_PATH)
+        .ok()
+        .and_then(|text| ron::from_str::<CampaignSave>(&text).ok())
+        .map_or(1, |save| save.unlocked)
+        .clamp(1, levels.len());
+
+    commands.insert_resource(Campaign {
+        current: unlocked - 1,
+        unlocked,
+        cleared: false,
+        levels,
+    });
+}
+
+impl Campaign {
+    fn level(&self) -> &CampaignLevel {
+        &self.levels[self.current]
+    }
+
+    /// Moves on to the next level and unlocks it. Returns `false` when the
+    /// cleared level was the last one.
+    fn advance(&mut self) -> bool {
+        if self.current + 1 == self.levels.len() {
+            return false;
+        }
+
+        self.current += 1;
+        self.cleared = true;
+
+        if self.current + 1 > self.unlocked {
+            self.unlocked = self.current + 1;
+            self.save();
+        }
+
+        true
+    }
+
+    fn save(&self) {
+        let save = CampaignSave {
+            unlocked: self.unlocked,
+        };
+
+        let result = ron::to_string(&save)
+            .map_err(|err| err.to_string())
+            .and_then(|text| fs::write(CAMPAIGN_SAVE_PATH, text).map_err(|err| err.to_string()));
+
+        if let Err(err) = result {
+            warn!("could not save the campaign progress to {CAMPAIGN_SAVE_PATH}: {err}");
+        }
+    }
+}
+
+/// Starts from the last unlocked level when coming from the menu
+fn start_campaign(mut campaign: ResMut<Campaign>) {
+    campaign.current = campaign.unlocked - 1;
+    campaign.cleared = false;
+}
+
+fn use_campaign_level(mut commands: Commands, campaign: Res<Campaign>) {
+    commands.insert_resource(LevelHandle(Some(campaign.level().handle.clone())));
+}
+
+/// Left and right browse the unlocked levels, the arena behind the screen
+/// follows the pick
+fn pick_level(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut campaign: ResMut<Campaign>,
+) {
+    let current = if keyboard_input.any_just_pressed([KeyCode::ArrowLeft, KeyCode::KeyA]) {
+        campaign.current.saturating_sub(1)
+    } else if keyboard_input.any_just_pressed([KeyCode::ArrowRight, KeyCode::KeyD]) {
+        (campaign.current + 1).min(campaign.unlocked - 1)
+    } else {
+        return;
+    };
+
+    if current != campaign.current {
+        campaign.current = current;
+        campaign.cleared = false;
+        commands.insert_resource(LevelHandle(Some(campaign.level().handle.clone())));
+    }
+}
+
+fn refresh_campaign_screen(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    campaign: Res<Campaign>,
+    screen_query: Query<Entity, With<CampaignScreen>>,
+) {
+    if !campaign.is_changed() && !screen_query.is_empty() {
+        return;
+    }
+
+    for screen in &screen_query {
+        commands.entity(screen).despawn_recursive();
+    }
+
+    let level = campaign.level();
+    let title = if campaign.cleared {
+        "Level Complete!"
+    } else {
+        "Campaign"
+    };
+
+    let screen = spawn_screen(
+        &mut commands,
+        &config,
+        GameState::LevelTransition,
+        title,
+        &format!(
+            "Level {}/{}: {}\nGoal: {}\nLeft/Right to pick among {} unlocked, Enter to play",
+            campaign.current + 1,
+            campaign.levels.len(),
+            level.name,
+            level.goal,
+            campaign.unlocked
+        ),
+    );
+    commands.entity(screen).insert(CampaignScreen);
+}
+
+fn check_level_goal(
+    apples_eaten: Res<ApplesEaten>,
+    elapsed_time: Res<ElapsedTime>,
+    mut campaign: ResMut<Campaign>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !campaign
+        .level()
+        .goal
+        .is_met(**apples_eaten, elapsed_time.elapsed_secs())
+    {
+        return;
+    }
+
+    if campaign.advance() {
+        next_state.set(GameState::LevelTransition);
+    } else {
+        next_state.set(GameState::Victory);
+    }
+}
@@ -0,0 +1,358 @@
+use std::fs;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::data::config::GameConfig;
+use crate::data::constants::{EDITOR_HUD_FONT_SIZE, EDITOR_LEVEL_PATH};
+use crate::simulation::grid::GridPos;
+use crate::simulation::level::{Level, PORTAL_TILES};
+use crate::utils::grid::ArenaGeometry;
+
+use super::game_state::GameState;
+use super::level::CurrentLevel;
+
+/// What a left click paints
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Brush {
+    Wall,
+    Portal,
+    Spawn,
+    Food,
+}
+
+impl Brush {
+    fn name(self) -> &'static str {
+        match self {
+            Brush::Wall => "Wall",
+            Brush::Portal => "Portal",
+            Brush::Spawn => "Spawn",
+            Brush::Food => "Food",
+        }
+    }
+}
+
+/// The editor paints straight into the current level, so walls and portals
+/// are spawned by their own plugins as it changes
+#[derive(Resource)]
+struct Editor {
+    brush: Brush,
+    // First end of the portal pair being placed
+    portal_entry: Option<GridPos>,
+    // Outcome of the last save or test-play
+    status: String,
+}
+
+#[derive(Component)]
+struct EditorHud;
+
+/// Bar holding the HUD text, clicks on it do not paint the cells under it
+#[derive(Component)]
+#[require(Interaction)]
+struct EditorHudBar;
+
+/// Sprite showing the snake, an apple, an enemy or a pending portal end
+#[derive(Component)]
+struct EditorMarker;
+
+pub fn editor_plugin(app: &mut App) {
+    app.insert_resource(Editor {
+        brush: Brush::Wall,
+        portal_entry: None,
+        status: String::new(),
+    })
+    .add_systems(
+        OnTransition {
+            exited: GameState::Menu,
+            entered: GameState::Editor,
+        },
+        start_editor,
+    )
+    .add_systems(OnEnter(GameState::Editor), spawn_editor_hud)
+    .add_systems(
+        Update,
+        (
+            pick_brush,
+            paint_cells,
+            editor_commands,
+            refresh_editor_markers,
+            update_editor_hud,
+        )
+            .chain()
+            .run_if(in_state(GameState::Editor)),
+    );
+}
+
+fn start_editor(mut editor: ResMut<Editor>) {
+    editor.portal_entry = None;
+    editor.status.clear();
+}
+
+fn spawn_editor_hud(mut commands: Commands, config: Res<GameConfig>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            BackgroundColor(*config.overlay.background_color),
+            EditorHudBar,
+            StateScoped(GameState::Editor),
+        ))
+        .with_child((
+            Text::default(),
+            TextFont {
+                font_size: EDITOR_HUD_FONT_SIZE,
+                ..default()
+            },
+            TextColor(*config.overlay.text_color),
+            EditorHud,
+        ));
+}
+
+fn pick_brush(keyboard_input: Res<ButtonInput<KeyCode>>, mut editor: ResMut<Editor>) {
+    let key_bindings = [
+        (KeyCode::Digit1, Brush::Wall),
+        (KeyCode::Digit2, Brush::Portal),
+        (KeyCode::Digit3, Brush::Spawn),
+        (KeyCode::Digit4, Brush::Food),
+    ];
+
+    for (key, brush) in key_bindings {
+        if keyboard_input.just_pressed(key) && editor.brush != brush {
+            editor.brush = brush;
+            editor.portal_entry = None;
+        }
+    }
+}
+
+/// Left click paints the brush on the cell under the mouse, right click
+/// erases it. Walls and erasing follow the mouse while the button is held.
+fn paint_cells(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    hud_bar: Single<&Interaction, With<EditorHudBar>>,
+    geometry: Res<ArenaGeometry>,
+    mut editor: ResMut<Editor>,
+    mut level: ResMut<CurrentLevel>,
+) {
+    if **hud_bar != Interaction::None {
+        return;
+    }
+
+    let (camera, camera_transform) = *camera;
+    let Some(cell) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+        .and_then(|position| geometry.translation_to_cell(position))
+    else {
+        return;
+    };
+
+    if mouse_input.pressed(MouseButton::Right) {
+        // Only touches the level when there is something to erase, so the
+        // arena is not rebuilt every frame
+        if level.bypass_change_detection().clear_cell(cell) {
+            level.set_changed();
+        }
+        if editor.portal_entry == Some(cell) {
+            editor.portal_entry = None;
+        }
+        return;
+    }
+
+    let painting = match editor.brush {
+        Brush::Wall => mouse_input.pressed(MouseButton::Left),
+        _ => mouse_input.just_pressed(MouseButton::Left),
+    };
+
+    // The snake is moved with the spawn brush only
+    if !painting || (editor.brush != Brush::Spawn && level.snake_cells().contains(&cell)) {
+        return;
+    }
+
+    match editor.brush {
+        Brush::Wall => {
+            if !level.walls.contains(&cell) {
+                level.clear_cell(cell);
+                level.walls.insert(cell);
+            }
+        }
+        Brush::Portal => match editor.portal_entry {
+            None if level.portals.len() >= PORTAL_TILES.count() => {
+                editor.status =
+                    format!("A level has at most {} portal pairs", PORTAL_TILES.count());
+            }
+            None => editor.portal_entry = Some(cell),
+            Some(entry) if entry == cell => {}
+            Some(entry) => {
+                level.clear_cell(entry);
+                level.clear_cell(cell);
+                level.portals.push((entry, cell));
+                editor.portal_entry = None;
+            }
+        },
+        Brush::Spawn => {
+            level.snake_start = cell;
+            for body_cell in level.snake_cells() {
+                level.clear_cell(body_cell);
+            }
+        }
+        Brush::Food => {
+            if !level.apples.contains(&cell) {
+                level.clear_cell(cell);
+                level.apples.push(cell);
+            }
+        }
+    }
+}
+
+fn editor_commands(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut editor: ResMut<Editor>,
+    mut level: ResMut<CurrentLevel>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyR) {
+        level.snake_direction = level.snake_direction.turn_right();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::KeyN) {
+        **level = Level::empty(
+            level.width,
+            level.height,
+            level.snake_start,
+            level.snake_length,
+            level.snake_direction,
+        );
+        editor.portal_entry = None;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        match level.validate() {
+            Ok(()) => {
+                editor.status.clear();
+                next_state.set(GameState::Playing);
+            }
+            Err(err) => editor.status = err.to_string(),
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::KeyS) {
+        let result = level
+            .to_ron()
+            .map_err(|err| err.to_string())
+            .and_then(|text| fs::write(EDITOR_LEVEL_PATH, text).map_err(|err| err.to_string()));
+
+        editor.status = match result {
+            Ok(()) => format!("Saved to {EDITOR_LEVEL_PATH}"),
+            Err(err) => format!("Could not save to {EDITOR_LEVEL_PATH}: {err}"),
+        };
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::Menu);
+    }
+}
+
+fn refresh_editor_markers(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+    geometry: Res<ArenaGeometry>,
+    level: Res<CurrentLevel>,
+    editor: Res<Editor>,
+    marker_query: Query<Entity, With<EditorMarker>>,
+) {
+    if !level.is_changed() && !editor.is_changed() && !marker_query.is_empty() {
+        return;
+    }
+
+    for marker in &marker_query {
+        commands.entity(marker).despawn();
+    }
+
+    let cell_size = Vec2::splat(geometry.cell_size);
+    let snake_cells = level.snake_cells();
+
+    for (i, &cell) in snake_cells.iter().enumerate() {
+        let sprite = if i == 0 {
+            "sprites/snake-head.png"
+        } else if i + 1 == snake_cells.len() {
+            "sprites/snake-tail.png"
+        } else {
+            "sprites/snake-body.png"
+        };
+
+        commands.spawn((
+            Sprite {
+                image: asset_server.load(sprite),
+                custom_size: Some(cell_size),
+                ..default()
+            },
+            Transform {
+                translation: geometry.cell_to_translation(cell, 1.0),
+                rotation: level.snake_direction.rotation(),
+                ..default()
+            },
+            EditorMarker,
+            StateScoped(GameState::Editor),
+        ));
+    }
+
+    for &cell in &level.apples {
+        commands.spawn((
+            Sprite {
+                image: asset_server.load(config.food.apple.sprite.clone()),
+                custom_size: Some(cell_size),
+                ..default()
+            },
+            Transform::from_translation(geometry.cell_to_translation(cell, 0.0)),
+            EditorMarker,
+            StateScoped(GameState::Editor),
+        ));
+    }
+
+    for hazard in &level.hazards {
+        commands.spawn((
+            Sprite::from_color(*config.hazards.color, cell_size * config.hazards.size),
+            Transform {
+                translation: geometry.cell_to_translation(hazard.cell, 0.5),
+                rotation: Quat::from_rotation_z(std::f32::consts::FRAC_PI_4),
+                ..default()
+            },
+            EditorMarker,
+            StateScoped(GameState::Editor),
+        ));
+    }
+
+    // Takes the color the pair will have once its exit is placed
+    if let Some(cell) = editor.portal_entry {
+        let colors = &config.arena.portal_colors;
+        let color = *colors[level.portals.len() % colors.len()];
+
+        commands.spawn((
+            Sprite::from_color(color, cell_size * config.arena.portal_size),
+            Transform::from_translation(geometry.cell_to_translation(cell, 0.0)),
+            EditorMarker,
+            StateScoped(GameState::Editor),
+        ));
+    }
+}
+
+fn update_editor_hud(editor: Res<Editor>, mut hud: Single<&mut Text, With<EditorHud>>) {
+    let text = format!(
+        "Brush: {} | 1 Wall  2 Portal  3 Spawn  4 Food | R turns the snake, N clears\n\
+         Left click paints, right click erases | Enter to test-play, S to save, Esc for the menu\n\
+         {}",
+        editor.brush.name(),
+        editor.status
+    );
+
+    if hud.0 != text {
+        hud.0 = text;
+    }
+}
@@ -0,0 +1,179 @@
+use bevy::prelude::*;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+use crate::data::config::GameConfig;
+use crate::data::constants::{FOOD_BLINK_INTERVAL, FOOD_COUNTDOWN_HEIGHT};
+use crate::simulation::board::Board;
+use crate::simulation::food::FoodKind;
+use crate::simulation::grid::GridPos;
+use crate::utils::grid::ArenaGeometry;
+
+use super::game_state::GameState;
+use super::rng::GameRng;
+use super::snake::{listener_get_food, GameEntity, SnakeBoard};
+
+/// Something the snake can eat. Food with a lifetime blinks before it
+/// vanishes, an apple then moves to another cell and any other kind makes
+/// room for an apple.
+#[derive(Component)]
+#[require(GameEntity)]
+pub struct Food {
+    pub kind: FoodKind,
+    pub cell: GridPos,
+    lifetime: Option<Timer>,
+}
+
+/// Bar under timed food, shrinking as its lifetime runs out
+#[derive(Component)]
+struct FoodCountdown;
+
+pub fn food_plugin(app: &mut App) {
+    // Food eaten on the tick its lifetime ends is despawned and replaced by
+    // `listener_get_food` only
+    app.add_systems(
+        Update,
+        (tick_food_lifetime, update_food_countdown)
+            .chain()
+            .after(listener_get_food)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
+
+/// Picks the next food kind from the spawn weights of the config
+fn roll_food_kind(config: &GameConfig, rng: &mut impl Rng) -> FoodKind {
+    let weights = FoodKind::ALL.map(|kind| config.food.kind(kind).weight);
+
+    // The config is validated to have at least one weight above zero
+    let index = WeightedIndex::new(weights).unwrap();
+    FoodKind::ALL[index.sample(rng)]
+}
+
+/// Places a random kind of food on the board and spawns it. Returns `None`
+/// when the snake covers every free cell.
+pub fn spawn_food(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    config: &GameConfig,
+    geometry: &ArenaGeometry,
+    board: &mut Board,
+    rng: &mut impl Rng,
+) -> Option<Entity> {
+    let kind = roll_food_kind(config, rng);
+
+    spawn_food_kind(commands, asset_server, config, geometry, board, rng, kind)
+}
+
+fn spawn_food_kind(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    config: &GameConfig,
+    geometry: &ArenaGeometry,
+    board: &mut Board,
+    rng: &mut impl Rng,
+    kind: FoodKind,
+) -> Option<Entity> {
+    let cell = board.place_food(kind, rng)?;
+    let kind_config = config.food.kind(kind);
+
+    let mut food_commands = commands.spawn((
+        Sprite {
+            image: asset_server.load(kind_config.sprite.clone()),
+            custom_size: Some(Vec2::splat(geometry.cell_size)),
+            ..default()
+        },
+        Transform::from_translation(geometry.cell_to_translation(cell, 0.0)),
+        Food {
+            kind,
+            cell,
+            lifetime: kind_config
+                .lifetime
+                .map(|seconds| Timer::from_seconds(seconds, TimerMode::Once)),
+        },
+    ));
+
+    if kind_config.lifetime.is_some() {
+        let bar_height = geometry.cell_size * FOOD_COUNTDOWN_HEIGHT;
+
+        food_commands.with_child((
+            Sprite {
+                color: *config.food.countdown_color,
+                custom_size: Some(Vec2::new(geometry.cell_size, bar_height)),
+                ..default()
+            },
+            Transform::from_xyz(0.0, (bar_height - geometry.cell_size) / 2.0, 0.5),
+            FoodCountdown,
+        ));
+    }
+
+    Some(food_commands.id())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn tick_food_lifetime(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut food_query: Query<(Entity, &mut Food, &mut Visibility)>,
+    mut board: ResMut<SnakeBoard>,
+    mut rng: ResMut<GameRng>,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+    geometry: Res<ArenaGeometry>,
+) {
+    for (food_entity, mut food, mut visibility) in &mut food_query {
+        // Already eaten, the board step removed it
+        if !board.has_food(food.cell) {
+            continue;
+        }
+
+        let Some(lifetime) = &mut food.lifetime else {
+            continue;
+        };
+
+        if !lifetime.tick(time.delta()).just_finished() {
+            let remaining = lifetime.remaining_secs();
+            let blink_off = remaining < config.food.blink_seconds
+                && (remaining / FOOD_BLINK_INTERVAL) as u32 % 2 == 0;
+
+            visibility.set_if_neq(if blink_off {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            });
+            continue;
+        }
+
+        board.remove_food(food.cell);
+        commands.entity(food_entity).despawn_recursive();
+        spawn_food_kind(
+            &mut commands,
+            &asset_server,
+            &config,
+            &geometry,
+            &mut board,
+            &mut **rng,
+            FoodKind::Apple,
+        );
+    }
+}
+
+fn update_food_countdown(
+    food_query: Query<(&Food, &Children)>,
+    mut countdown_query: Query<&mut Transform, With<FoodCountdown>>,
+    geometry: Res<ArenaGeometry>,
+) {
+    for (food, children) in &food_query {
+        let Some(lifetime) = &food.lifetime else {
+            continue;
+        };
+        let left = lifetime.fraction_remaining();
+
+        for &child in children {
+            if let Ok(mut bar_transform) = countdown_query.get_mut(child) {
+                // Shrinks towards the left edge of the cell
+                bar_transform.scale.x = left;
+                bar_transform.translation.x = -geometry.cell_size * (1.0 - left) / 2.0;
+            }
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use bevy::{ecs::system::EntityCommands, prelude::*};
+
+use crate::data::config::GameConfig;
+
+use super::score::{ElapsedTime, Score};
+
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GameState {
+    #[default]
+    Menu,
+    Playing,
+    Paused,
+    // Counting down before play resumes after a pause
+    Resuming,
+    GameOver,
+    Victory,
+    // Between two campaign levels
+    LevelTransition,
+    // The time attack clock ran out
+    TimeUp,
+    // Painting a level with the mouse
+    Editor,
+}
+
+/// Rules of the current run, picked from the menu
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    #[default]
+    Endless,
+    Campaign,
+    // Eat as much as possible before the clock runs out
+    TimeAttack,
+    // Test-playing the level of the editor
+    Editor,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 4] = [
+        GameMode::Endless,
+        GameMode::Campaign,
+        GameMode::TimeAttack,
+        GameMode::Editor,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Endless => "Endless",
+            GameMode::Campaign => "Campaign",
+            GameMode::TimeAttack => "Time Attack",
+            GameMode::Editor => "Editor",
+        }
+    }
+
+    /// State the menu goes to when playing this mode
+    pub fn start_state(self) -> GameState {
+        match self {
+            GameMode::Endless | GameMode::TimeAttack => GameState::Playing,
+            GameMode::Campaign => GameState::LevelTransition,
+            GameMode::Editor => GameState::Editor,
+        }
+    }
+
+    /// State a run of this mode is left for
+    pub fn exit_state(self) -> GameState {
+        match self {
+            GameMode::Editor => GameState::Editor,
+            _ => GameState::Menu,
+        }
+    }
+}
+
+/// Entry of an overlay screen, its position counted from the top
+#[derive(Component)]
+#[require(Button)]
+pub struct ScreenButton(usize);
+
+/// Overlay screen entry highlighted with the arrows or the mouse
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct ButtonSelection(pub usize);
+
+/// An overlay screen entry was picked with Enter, Space or a click
+#[derive(Event)]
+pub struct ButtonPickedEvent(pub Entity);
+
+pub fn game_state_plugin(app: &mut App) {
+    app.init_state::<GameState>()
+        .init_resource::<GameMode>()
+        .init_resource::<ButtonSelection>()
+        .add_event::<ButtonPickedEvent>()
+        .enable_state_scoped_entities::<GameState>()
+        .add_systems(Update, (navigate_buttons, highlight_buttons).chain())
+        .add_systems(OnEnter(GameState::GameOver), spawn_game_over_screen)
+        .add_systems(OnEnter(GameState::Victory), spawn_victory_screen)
+        .add_systems(OnEnter(GameState::TimeUp), spawn_time_up_screen)
+        .add_systems(
+            Update,
+            (
+                start_game.run_if(
+                    in_state(GameState::GameOver)
+                        .or(in_state(GameState::Victory))
+                        .or(in_state(GameState::LevelTransition))
+                        .or(in_state(GameState::TimeUp)),
+                ),
+                back_to_menu.run_if(
+                    in_state(GameState::GameOver)
+                        .or(in_state(GameState::Victory))
+                        .or(in_state(GameState::LevelTransition))
+                        .or(in_state(GameState::TimeUp)),
+                ),
+            ),
+        );
+}
+
+/// Spawns a full screen overlay living as long as `state`, returning its
+/// root node
+pub fn spawn_screen(
+    commands: &mut Commands,
+    config: &GameConfig,
+    state: GameState,
+    title: &str,
+    hint: &str,
+) -> Entity {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(config.overlay.row_gap),
+                ..default()
+            },
+            BackgroundColor(*config.overlay.background_color),
+            StateScoped(state),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(title),
+                TextFont {
+                    font_size: config.overlay.title_font_size,
+                    ..default()
+                },
+                TextColor(*config.overlay.text_color),
+            ));
+            parent.spawn((
+                Text::new(hint),
+                TextFont {
+                    font_size: config.overlay.hint_font_size,
+                    ..default()
+                },
+                TextColor(*config.overlay.text_color),
+            ));
+        })
+        .id()
+}
+
+/// Spawns an overlay screen entry under `parent`, `index` counting the
+/// entries above it
+pub fn spawn_button<'a>(
+    parent: &'a mut ChildBuilder,
+    config: &GameConfig,
+    index: usize,
+    label: impl Into<String>,
+) -> EntityCommands<'a> {
+    let mut button = parent.spawn((
+        Node {
+            width: Val::Px(config.overlay.button_width),
+            padding: UiRect::all(Val::Px(config.overlay.button_padding)),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        BackgroundColor(*config.overlay.button_color),
+        ScreenButton(index),
+    ));
+
+    button.with_child((
+        Text::new(label),
+        TextFont {
+            font_size: config.overlay.hint_font_size,
+            ..default()
+        },
+        TextColor(*config.overlay.text_color),
+    ));
+
+    button
+}
+
+/// Up and down or the mouse move the highlight, Enter, Space or a click
+/// pick the entry
+fn navigate_buttons(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    interaction_query: Query<(Entity, &Interaction, &ScreenButton), Changed<Interaction>>,
+    button_query: Query<(Entity, &ScreenButton)>,
+    mut selection: ResMut<ButtonSelection>,
+    mut button_picked_event_writer: EventWriter<ButtonPickedEvent>,
+) {
+    let count = button_query.iter().count();
+    if count == 0 {
+        return;
+    }
+
+    if keyboard_input.any_just_pressed([KeyCode::ArrowUp, KeyCode::KeyW]) {
+        **selection = (**selection + count - 1) % count;
+    }
+    if keyboard_input.any_just_pressed([KeyCode::ArrowDown, KeyCode::KeyS]) {
+        **selection = (**selection + 1) % count;
+    }
+
+    for (button_entity, interaction, &ScreenButton(index)) in &interaction_query {
+        match interaction {
+            Interaction::Pressed => {
+                **selection = index;
+                button_picked_event_writer.send(ButtonPickedEvent(button_entity));
+            }
+            Interaction::Hovered => **selection = index,
+            Interaction::None => {}
+        }
+    }
+
+    if keyboard_input.any_just_pressed([KeyCode::Enter, KeyCode::Space]) {
+        if let Some((button_entity, _)) = button_query
+            .iter()
+            .find(|(_, &ScreenButton(index))| index == **selection)
+        {
+            button_picked_event_writer.send(ButtonPickedEvent(button_entity));
+        }
+    }
+}
+
+fn highlight_buttons(
+    config: Res<GameConfig>,
+    selection: Res<ButtonSelection>,
+    mut button_query: Query<(&ScreenButton, &mut BackgroundColor)>,
+) {
+    for (&ScreenButton(index), mut background_color) in &mut button_query {
+        let color = if index == **selection {
+            *config.overlay.selected_button_color
+        } else {
+            *config.overlay.button_color
+        };
+
+        background_color.set_if_neq(BackgroundColor(color));
+    }
+}
+
+/// What Enter and Esc do once a run is over
+fn replay_hint(game_mode: GameMode) -> &'static str {
+    match game_mode {
+        GameMode::Editor => "Press Enter to play again or Esc for the editor",
+        _ => "Press Enter to play again or Esc for the menu",
+    }
+}
+
+fn spawn_game_over_screen(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    game_mode: Res<GameMode>,
+) {
+    spawn_screen(
+        &mut commands,
+        &config,
+        GameState::GameOver,
+        "Game Over",
+        replay_hint(*game_mode),
+    );
+}
+
+fn spawn_victory_screen(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    score: Res<Score>,
+    elapsed_time: Res<ElapsedTime>,
+    game_mode: Res<GameMode>,
+) {
+    let seconds = elapsed_time.elapsed().as_secs();
+
+    spawn_screen(
+        &mut commands,
+        &config,
+        GameState::Victory,
+        "You Win!",
+        &format!(
+            "Score: {}  Time: {:02}:{:02}\n{}",
+            **score,
+            seconds / 60,
+            seconds % 60,
+            replay_hint(*game_mode)
+        ),
+    );
+}
+
+fn spawn_time_up_screen(mut commands: Commands, config: Res<GameConfig>, score: Res<Score>) {
+    spawn_screen(
+        &mut commands,
+        &config,
+        GameState::TimeUp,
+        "Time's Up!",
+        &format!(
+            "Score: {}\nPress Enter to play again or Esc for the menu",
+            **score
+        ),
+    );
+}
+
+fn start_game(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        next_state.set(GameState::Playing);
+    }
+}
+
+/// Esc leaves the run, back to the editor when test-playing its level
+fn back_to_menu(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    game_mode: Res<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(game_mode.exit_state());
+    }
+}
@@ -0,0 +1,71 @@
+use std::fs;
+
+use bevy::prelude::*;
+
+use crate::data::constants::HIGH_SCORES_PATH;
+use crate::simulation::high_scores::{self, HighScores};
+
+use super::game_state::{GameMode, GameState};
+use super::score::Score;
+
+/// Best scores of each mode, saved to `high_scores.ron`
+#[derive(Resource, Deref, DerefMut)]
+pub struct BestScores(HighScores);
+
+pub fn high_scores_plugin(app: &mut App) {
+    app.add_systems(Startup, load_high_scores)
+        .add_systems(OnEnter(GameState::GameOver), record_score)
+        .add_systems(OnEnter(GameState::Victory), record_score)
+        .add_systems(OnEnter(GameState::TimeUp), record_score);
+}
+
+impl BestScores {
+    /// Table of a mode, `None` for the editor whose levels change
+    pub fn table(&self, game_mode: GameMode) -> Option<&Vec<usize>> {
+        match game_mode {
+            GameMode::Endless => Some(&self.endless),
+            GameMode::Campaign => Some(&self.campaign),
+            GameMode::TimeAttack => Some(&self.time_attack),
+            GameMode::Editor => None,
+        }
+    }
+
+    fn table_mut(&mut self, game_mode: GameMode) -> Option<&mut Vec<usize>> {
+        match game_mode {
+            GameMode::Endless => Some(&mut self.endless),
+            GameMode::Campaign => Some(&mut self.campaign),
+            GameMode::TimeAttack => Some(&mut self.time_attack),
+            GameMode::Editor => None,
+        }
+    }
+
+    fn save(&self) {
+        let result = ron::to_string(&self.0)
+            .map_err(|err| err.to_string())
+            .and_then(|text| fs::write(HIGH_SCORES_PATH, text).map_err(|err| err.to_string()));
+
+        if let Err(err) = result {
+            warn!("could not save the high scores to {HIGH_SCORES_PATH}: {err}");
+        }
+    }
+}
+
+fn load_high_scores(mut commands: Commands) {
+    // Missing or broken high scores start over
+    let high_scores = fs::read_to_string(HIGH_SCORES_PATH)
+        .ok()
+        .and_then(|text| ron::from_str(&text).ok())
+        .unwrap_or_default();
+
+    commands.insert_resource(BestScores(high_scores));
+}
+
+fn record_score(score: Res<Score>, game_mode: Res<GameMode>, mut best_scores: ResMut<BestScores>) {
+    let Some(table) = best_scores.table_mut(*game_mode) else {
+        return;
+    };
+
+    if high_scores::record(table, **score) {
+        best_scores.save();
+    }
+}
@@ -0,0 +1,220 @@
+use std::{fmt, io};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+
+use crate::data::args::LaunchArgs;
+use crate::data::config::GameConfig;
+use crate::simulation::grid::{Direction, GridPos};
+use crate::simulation::level::{Level, LevelError};
+use crate::simulation::maze::{self, MazeDensity, MazeStyle};
+use crate::utils::grid::ArenaGeometry;
+
+use super::game_state::GameMode;
+use super::rng::GameRng;
+
+/// Level being played. Replacing it rebuilds the arena.
+#[derive(Resource, Deref, DerefMut)]
+pub struct CurrentLevel(Level);
+
+/// A level file loaded through the `AssetServer`
+#[derive(Asset, TypePath, Deref)]
+pub struct LevelAsset(Level);
+
+/// Level file to play, watched for changes. `None` plays the open arena
+/// described by the config.
+#[derive(Resource)]
+pub struct LevelHandle(pub Option<Handle<LevelAsset>>);
+
+/// Level of the endless and time attack modes, picked with `--level`
+#[derive(Resource)]
+struct EndlessLevel(Option<Handle<LevelAsset>>);
+
+/// Random walls added to the endless mode level
+#[derive(Resource, Clone, Copy)]
+pub struct MazeSettings {
+    pub style: Option<MazeStyle>,
+    pub density: MazeDensity,
+    pub safe_corridor: usize,
+}
+
+#[derive(Default)]
+struct LevelLoader;
+
+#[derive(Debug)]
+enum LevelLoaderError {
+    Io(io::Error),
+    Utf8(std::string::FromUtf8Error),
+    Level(LevelError),
+}
+
+pub fn level_plugin(app: &mut App) {
+    let config = app.world().resource::<GameConfig>();
+    let level = default_level(config);
+    let geometry = ArenaGeometry::new(config, level.width, level.height);
+    let maze_settings = MazeSettings::new(config, app.world().resource::<LaunchArgs>());
+
+    app.init_asset::<LevelAsset>()
+        .init_asset_loader::<LevelLoader>()
+        .insert_resource(CurrentLevel(level))
+        .insert_resource(geometry)
+        .insert_resource(LevelHandle(None))
+        .insert_resource(maze_settings)
+        .add_systems(Startup, load_level_from_args)
+        .add_systems(
+            Update,
+            (
+                use_endless_level.run_if(resource_changed::<GameMode>),
+                apply_loaded_level,
+            )
+                .chain(),
+        );
+}
+
+impl MazeSettings {
+    /// Reads the config, overridden by `--maze <style>` and
+    /// `--density <density>`
+    fn new(config: &GameConfig, launch_args: &LaunchArgs) -> Self {
+        Self {
+            style: launch_args.maze_style.or(config.maze.style),
+            density: launch_args.maze_density.unwrap_or(config.maze.density),
+            safe_corridor: config.maze.safe_corridor,
+        }
+    }
+}
+
+/// The open arena described by the config
+fn default_level(config: &GameConfig) -> Level {
+    let (start_x, start_y) = config.snake.start_cell;
+
+    Level::empty(
+        config.cell_columns(),
+        config.cell_rows(),
+        GridPos::new(start_x, start_y),
+        2,
+        Direction::Right,
+    )
+}
+
+/// Loads the level given with `--level <path>`, relative to the assets folder
+fn load_level_from_args(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    launch_args: Res<LaunchArgs>,
+) {
+    let level = launch_args
+        .level_path
+        .clone()
+        .map(|path| asset_server.load(path));
+
+    commands.insert_resource(EndlessLevel(level));
+}
+
+fn use_endless_level(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    endless_level: Res<EndlessLevel>,
+) {
+    if matches!(*game_mode, GameMode::Endless | GameMode::TimeAttack) {
+        commands.insert_resource(LevelHandle(endless_level.0.clone()));
+    }
+}
+
+/// Makes the level to play the current level when it is picked, once its
+/// file loads and every time the file changes on disk. Outside of the
+/// campaign the level gets the random walls of the maze settings.
+#[allow(clippy::too_many_arguments)]
+fn apply_loaded_level(
+    mut commands: Commands,
+    mut event_reader: EventReader<AssetEvent<LevelAsset>>,
+    level_handle: Res<LevelHandle>,
+    levels: Res<Assets<LevelAsset>>,
+    config: Res<GameConfig>,
+    game_mode: Res<GameMode>,
+    maze_settings: Res<MazeSettings>,
+    mut rng: ResMut<GameRng>,
+) {
+    let file_changed = event_reader
+        .read()
+        .filter(|event| {
+            level_handle.0.as_ref().is_some_and(|handle| {
+                event.is_loaded_with_dependencies(handle) || event.is_modified(handle)
+            })
+        })
+        .count()
+        > 0;
+
+    if !file_changed && !level_handle.is_changed() && !maze_settings.is_changed() {
+        return;
+    }
+
+    // The editor changes the current level in place
+    if *game_mode == GameMode::Editor {
+        return;
+    }
+
+    let level = match &level_handle.0 {
+        Some(handle) => match levels.get(handle) {
+            Some(level) => level.0.clone(),
+            // Applied once the file loads
+            None => return,
+        },
+        None => default_level(&config),
+    };
+
+    let level = match maze_settings.style {
+        Some(style) if *game_mode != GameMode::Campaign => maze::generate(
+            &level,
+            style,
+            maze_settings.density,
+            maze_settings.safe_corridor,
+            &mut **rng,
+        ),
+        _ => level,
+    };
+
+    commands.insert_resource(ArenaGeometry::new(&config, level.width, level.height));
+    commands.insert_resource(CurrentLevel(level));
+}
+
+impl AssetLoader for LevelLoader {
+    type Asset = LevelAsset;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<LevelAsset, LevelLoaderError> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(LevelLoaderError::Io)?;
+
+        let text = String::from_utf8(bytes).map_err(LevelLoaderError::Utf8)?;
+        let level = Level::from_ron(&text).map_err(LevelLoaderError::Level)?;
+
+        Ok(LevelAsset(level))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+impl fmt::Display for LevelLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelLoaderError::Io(err) => write!(f, "could not read the level file: {err}"),
+            LevelLoaderError::Utf8(err) => write!(f, "the level file is not UTF-8: {err}"),
+            LevelLoaderError::Level(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for LevelLoaderError {}
@@ -0,0 +1,295 @@
+use bevy::prelude::*;
+
+use crate::data::config::GameConfig;
+use crate::simulation::difficulty::Difficulty;
+use crate::simulation::maze::{MazeDensity, MazeStyle};
+
+use super::campaign::Campaign;
+use super::game_state::{
+    spawn_button, spawn_screen, ButtonPickedEvent, ButtonSelection, GameMode, GameState,
+};
+use super::high_scores::BestScores;
+use super::level::MazeSettings;
+use super::snake::SelectedDifficulty;
+use super::wall::ArenaMode;
+
+/// Page of the title screen
+#[derive(SubStates, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[source(GameState = GameState::Menu)]
+enum MenuPage {
+    #[default]
+    Main,
+    Modes,
+    Settings,
+    HighScores,
+}
+
+/// What a menu entry does when picked
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MenuAction {
+    Play,
+    Open(MenuPage),
+    PickMode(GameMode),
+    CycleDifficulty,
+    ToggleArenaMode,
+    CycleMazeStyle,
+    CycleMazeDensity,
+    Back,
+    Quit,
+}
+
+#[derive(Component)]
+struct MenuButton(MenuAction);
+
+/// Root node of the page on screen
+#[derive(Component)]
+struct MenuRoot;
+
+pub fn menu_plugin(app: &mut App) {
+    app.add_sub_state::<MenuPage>().add_systems(
+        Update,
+        (refresh_menu_page, apply_menu_action)
+            .chain()
+            .run_if(in_state(GameState::Menu)),
+    );
+}
+
+impl MenuPage {
+    fn title(self) -> &'static str {
+        match self {
+            MenuPage::Main => "Snake",
+            MenuPage::Modes => "Mode Select",
+            MenuPage::Settings => "Settings",
+            MenuPage::HighScores => "High Scores",
+        }
+    }
+}
+
+/// Entries of a page with their labels, which show the current settings.
+/// The campaign is left out of the modes when its file could not be loaded.
+fn page_entries(
+    page: MenuPage,
+    game_mode: GameMode,
+    difficulty: Difficulty,
+    arena_mode: ArenaMode,
+    maze_settings: MazeSettings,
+    campaign_loaded: bool,
+) -> Vec<(String, MenuAction)> {
+    match page {
+        MenuPage::Main => vec![
+            (format!("Play: {}", game_mode.name()), MenuAction::Play),
+            ("Mode Select".into(), MenuAction::Open(MenuPage::Modes)),
+            ("Settings".into(), MenuAction::Open(MenuPage::Settings)),
+            ("High Scores".into(), MenuAction::Open(MenuPage::HighScores)),
+            ("Quit".into(), MenuAction::Quit),
+        ],
+        MenuPage::Modes => GameMode::ALL
+            .into_iter()
+            .filter(|&mode| mode != GameMode::Campaign || campaign_loaded)
+            .map(|mode| (mode.name().to_string(), MenuAction::PickMode(mode)))
+            .chain([("Back".into(), MenuAction::Back)])
+            .collect(),
+        MenuPage::Settings => vec![
+            (
+                format!("Difficulty: {}", difficulty.name()),
+                MenuAction::CycleDifficulty,
+            ),
+            (
+                match arena_mode {
+                    ArenaMode::Walls => "Arena: Walls".into(),
+                    ArenaMode::Wrap => "Arena: Wrap Around".into(),
+                },
+                MenuAction::ToggleArenaMode,
+            ),
+            (
+                format!(
+                    "Maze: {}",
+                    maze_settings.style.map_or("Off", MazeStyle::name)
+                ),
+                MenuAction::CycleMazeStyle,
+            ),
+            (
+                format!("Maze Density: {}", maze_settings.density.name()),
+                MenuAction::CycleMazeDensity,
+            ),
+            ("Back".into(), MenuAction::Back),
+        ],
+        MenuPage::HighScores => vec![("Back".into(), MenuAction::Back)],
+    }
+}
+
+/// Text under the title of a page
+fn page_hint(page: MenuPage, best_scores: &BestScores) -> String {
+    match page {
+        MenuPage::Main => "Arrows and Enter or the mouse to pick".into(),
+        MenuPage::Modes => "Pick the mode to play".into(),
+        MenuPage::Settings => "Applied from the next run".into(),
+        MenuPage::HighScores => GameMode::ALL
+            .into_iter()
+            .filter_map(|mode| {
+                let table = best_scores.table(mode)?;
+                let scores = if table.is_empty() {
+                    "-".to_string()
+                } else {
+                    table
+                        .iter()
+                        .map(|score| score.to_string())
+                        .collect::<Vec<_>>()
+                        .join("  ")
+                };
+
+                Some(format!("{}: {scores}", mode.name()))
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+/// Rebuilds the page when it opens and when a setting it shows changes
+#[allow(clippy::too_many_arguments)]
+fn refresh_menu_page(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    page: Res<State<MenuPage>>,
+    game_mode: Res<GameMode>,
+    difficulty: Res<SelectedDifficulty>,
+    arena_mode: Res<ArenaMode>,
+    maze_settings: Res<MazeSettings>,
+    best_scores: Res<BestScores>,
+    campaign: Option<Res<Campaign>>,
+    mut selection: ResMut<ButtonSelection>,
+    root_query: Query<Entity, With<MenuRoot>>,
+) {
+    let settings_changed = page.is_changed()
+        || game_mode.is_changed()
+        || difficulty.is_changed()
+        || arena_mode.is_changed()
+        || maze_settings.is_changed()
+        || best_scores.is_changed();
+
+    if !settings_changed && !root_query.is_empty() {
+        return;
+    }
+
+    for root in &root_query {
+        commands.entity(root).despawn_recursive();
+    }
+
+    // A page opens on its first entry
+    if page.is_changed() {
+        **selection = 0;
+    }
+
+    let page = *page.get();
+    let root = spawn_screen(
+        &mut commands,
+        &config,
+        GameState::Menu,
+        page.title(),
+        &page_hint(page, &best_scores),
+    );
+
+    let entries = page_entries(
+        page,
+        *game_mode,
+        **difficulty,
+        *arena_mode,
+        *maze_settings,
+        campaign.is_some(),
+    );
+
+    commands
+        .entity(root)
+        .insert(MenuRoot)
+        .with_children(|parent| {
+            for (index, (label, action)) in entries.into_iter().enumerate() {
+                spawn_button(parent, &config, index, label).insert(MenuButton(action));
+            }
+
+            if page == MenuPage::Main {
+                parent.spawn((
+                    Text::new("Made by: @Cova-ops"),
+                    TextFont {
+                        font_size: config.credits.font_size,
+                        ..default()
+                    },
+                    TextColor(*config.credits.font_color),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        bottom: Val::Px(config.credits.bottom_padding),
+                        right: Val::Px(config.credits.right_padding),
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+/// Runs the picked entry. Esc goes back from the other pages to the main one.
+#[allow(clippy::too_many_arguments)]
+fn apply_menu_action(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut event_reader: EventReader<ButtonPickedEvent>,
+    button_query: Query<&MenuButton>,
+    page: Res<State<MenuPage>>,
+    mut game_mode: ResMut<GameMode>,
+    mut difficulty: ResMut<SelectedDifficulty>,
+    mut arena_mode: ResMut<ArenaMode>,
+    mut maze_settings: ResMut<MazeSettings>,
+    mut next_page: ResMut<NextState<MenuPage>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut app_exit_event_writer: EventWriter<AppExit>,
+) {
+    let mut actions: Vec<MenuAction> = event_reader
+        .read()
+        .filter_map(|&ButtonPickedEvent(entity)| button_query.get(entity).ok())
+        .map(|&MenuButton(action)| action)
+        .collect();
+
+    if keyboard_input.just_pressed(KeyCode::Escape) && *page.get() != MenuPage::Main {
+        actions.push(MenuAction::Back);
+    }
+
+    for action in actions {
+        match action {
+            MenuAction::Play => next_state.set(game_mode.start_state()),
+            MenuAction::Open(page) => next_page.set(page),
+            MenuAction::PickMode(mode) => {
+                game_mode.set_if_neq(mode);
+                next_page.set(MenuPage::Main);
+            }
+            MenuAction::CycleDifficulty => {
+                let index = Difficulty::ALL.iter().position(|&d| d == **difficulty);
+                let next = index.map_or(0, |index| (index + 1) % Difficulty::ALL.len());
+                **difficulty = Difficulty::ALL[next];
+            }
+            MenuAction::ToggleArenaMode => {
+                *arena_mode = match *arena_mode {
+                    ArenaMode::Walls => ArenaMode::Wrap,
+                    ArenaMode::Wrap => ArenaMode::Walls,
+                };
+            }
+            // Off, then each style in turn
+            MenuAction::CycleMazeStyle => {
+                let index = maze_settings
+                    .style
+                    .and_then(|style| MazeStyle::ALL.iter().position(|&s| s == style));
+                maze_settings.style = match index {
+                    None => Some(MazeStyle::ALL[0]),
+                    Some(index) => MazeStyle::ALL.get(index + 1).copied(),
+                };
+            }
+            MenuAction::CycleMazeDensity => {
+                let index = MazeDensity::ALL
+                    .iter()
+                    .position(|&d| d == maze_settings.density);
+                let next = index.map_or(0, |index| (index + 1) % MazeDensity::ALL.len());
+                maze_settings.density = MazeDensity::ALL[next];
+            }
+            MenuAction::Back => next_page.set(MenuPage::Main),
+            MenuAction::Quit => {
+                app_exit_event_writer.send(AppExit::Success);
+            }
+        }
+    }
+}
@@ -1,5 +1,16 @@
 pub mod background;
 pub mod camera;
+pub mod campaign;
+pub mod editor;
+pub mod food;
+pub mod game_state;
+pub mod high_scores;
+pub mod level;
+pub mod menu;
+pub mod pause;
+pub mod portal;
+pub mod rng;
 pub mod score;
 pub mod snake;
+pub mod time_attack;
 pub mod wall;
@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+
+use crate::data::config::GameConfig;
+
+use super::game_state::{
+    spawn_button, spawn_screen, ButtonPickedEvent, ButtonSelection, GameMode, GameState,
+};
+
+/// What a pause screen entry does when picked
+#[derive(Component, Clone, Copy)]
+enum PauseButton {
+    Resume,
+    Restart,
+    Quit,
+}
+
+/// Time left before play resumes and the second on screen
+#[derive(Resource)]
+struct ResumeCountdown {
+    timer: Timer,
+    shown: u32,
+}
+
+#[derive(Component)]
+struct CountdownScreen;
+
+/// The snake and every other timer only run while `Playing`, so the
+/// `Paused` and `Resuming` states freeze the run and ignore turns pressed
+/// meanwhile.
+pub fn pause_plugin(app: &mut App) {
+    let countdown_seconds = app
+        .world()
+        .resource::<GameConfig>()
+        .overlay
+        .resume_countdown_seconds;
+
+    app.insert_resource(ResumeCountdown {
+        timer: Timer::from_seconds(countdown_seconds, TimerMode::Once),
+        shown: 0,
+    })
+    .add_systems(OnEnter(GameState::Paused), spawn_pause_screen)
+    .add_systems(OnEnter(GameState::Resuming), start_countdown)
+    .add_systems(
+        Update,
+        (
+            toggle_pause.run_if(in_state(GameState::Playing).or(in_state(GameState::Paused))),
+            apply_pause_action.run_if(in_state(GameState::Paused)),
+            tick_countdown.run_if(in_state(GameState::Resuming)),
+        ),
+    );
+}
+
+fn spawn_pause_screen(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    game_mode: Res<GameMode>,
+    mut selection: ResMut<ButtonSelection>,
+) {
+    let quit_label = match game_mode.exit_state() {
+        GameState::Editor => "Quit to Editor",
+        _ => "Quit to Menu",
+    };
+
+    let screen = spawn_screen(
+        &mut commands,
+        &config,
+        GameState::Paused,
+        "Paused",
+        "Press P or Esc to resume",
+    );
+
+    commands.entity(screen).with_children(|parent| {
+        let buttons = [
+            ("Resume", PauseButton::Resume),
+            ("Restart", PauseButton::Restart),
+            (quit_label, PauseButton::Quit),
+        ];
+
+        for (index, (label, action)) in buttons.into_iter().enumerate() {
+            spawn_button(parent, &config, index, label).insert(action);
+        }
+    });
+
+    **selection = 0;
+}
+
+fn toggle_pause(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keyboard_input.any_just_pressed([KeyCode::KeyP, KeyCode::Escape]) {
+        return;
+    }
+
+    match state.get() {
+        GameState::Playing => next_state.set(GameState::Paused),
+        GameState::Paused => next_state.set(GameState::Resuming),
+        _ => {}
+    }
+}
+
+/// Restarting goes straight back to `Playing`, where the run is rebuilt
+fn apply_pause_action(
+    mut event_reader: EventReader<ButtonPickedEvent>,
+    button_query: Query<&PauseButton>,
+    game_mode: Res<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for &ButtonPickedEvent(entity) in event_reader.read() {
+        let Ok(&action) = button_query.get(entity) else {
+            continue;
+        };
+
+        match action {
+            PauseButton::Resume => next_state.set(GameState::Resuming),
+            PauseButton::Restart => next_state.set(GameState::Playing),
+            PauseButton::Quit => next_state.set(game_mode.exit_state()),
+        }
+    }
+}
+
+fn start_countdown(mut countdown: ResMut<ResumeCountdown>) {
+    countdown.timer.reset();
+    countdown.shown = 0;
+}
+
+/// Shows the seconds left as 3, 2, 1 and resumes play once they run out
+fn tick_countdown(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    mut countdown: ResMut<ResumeCountdown>,
+    screen_query: Query<Entity, With<CountdownScreen>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if countdown.timer.tick(time.delta()).finished() {
+        next_state.set(GameState::Playing);
+        return;
+    }
+
+    let seconds = countdown.timer.remaining_secs().ceil() as u32;
+    if seconds == countdown.shown {
+        return;
+    }
+    countdown.shown = seconds;
+
+    for screen in &screen_query {
+        commands.entity(screen).despawn_recursive();
+    }
+
+    let screen = spawn_screen(
+        &mut commands,
+        &config,
+        GameState::Resuming,
+        &seconds.to_string(),
+        "Get ready",
+    );
+    commands.entity(screen).insert(CountdownScreen);
+}
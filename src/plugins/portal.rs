@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+
+use crate::data::config::GameConfig;
+use crate::utils::grid::ArenaGeometry;
+
+use super::level::CurrentLevel;
+
+#[derive(Component)]
+#[require(Sprite, Transform)]
+pub struct Portal;
+
+/// Spawns both ends of every portal pair of the level, each pair with its
+/// own color
+fn init_portals(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    geometry: Res<ArenaGeometry>,
+    level: Res<CurrentLevel>,
+    portal_query: Query<Entity, With<Portal>>,
+) {
+    for portal_entity in &portal_query {
+        commands.entity(portal_entity).despawn();
+    }
+
+    let colors = config.arena.portal_colors.iter().cycle();
+
+    for (&(entry, exit), color) in level.portals.iter().zip(colors) {
+        for cell in [entry, exit] {
+            commands.spawn((
+                Portal,
+                Sprite::from_color(
+                    **color,
+                    Vec2::splat(geometry.cell_size * config.arena.portal_size),
+                ),
+                Transform::from_translation(geometry.cell_to_translation(cell, 0.0)),
+            ));
+        }
+    }
+}
+
+pub fn portal_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        init_portals.run_if(resource_changed::<CurrentLevel>),
+    );
+}
@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::data::args::LaunchArgs;
+use crate::data::config::GameConfig;
+
+/// Source of every random decision of a run. Each run starts from its own
+/// logged seed, so the same seed and the same inputs replay the same game.
+#[derive(Resource, Deref, DerefMut)]
+pub struct GameRng {
+    // Seed given with `--seed` or in the config, every run restarts from it
+    fixed_seed: Option<u64>,
+    #[deref]
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(fixed_seed: Option<u64>) -> Self {
+        let mut game_rng = Self {
+            fixed_seed,
+            rng: StdRng::seed_from_u64(0),
+        };
+        game_rng.reset();
+        game_rng
+    }
+
+    /// Restarts from the fixed seed, or else from a fresh random one
+    pub fn reset(&mut self) {
+        let seed = self.fixed_seed.unwrap_or_else(rand::random);
+        info!("Run seed: {seed}");
+
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
+
+pub fn rng_plugin(app: &mut App) {
+    let fixed_seed = app
+        .world()
+        .resource::<LaunchArgs>()
+        .seed
+        .or(app.world().resource::<GameConfig>().seed);
+
+    app.insert_resource(GameRng::new(fixed_seed));
+}
@@ -1,50 +1,83 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, time::Stopwatch};
 
-use crate::{data::colors::SCOREBOARD_TEXT_COLOR, data::constants::*};
+use crate::data::config::GameConfig;
+
+use super::game_state::{GameMode, GameState};
+use super::snake::SnakeSpeed;
+use super::time_attack::TimeLeft;
 
 #[derive(Resource, Deref, DerefMut)]
 pub struct Score(usize);
 
+// Apples eaten in the current run, golden ones included. Unlike the score,
+// rotten food and hazard penalties leave it alone.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct ApplesEaten(usize);
+
+// Time spent playing the current run, pauses excluded
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct ElapsedTime(Stopwatch);
+
 #[derive(Component)]
 struct ScoreboardUi;
 
 pub fn score_plugin(app: &mut App) {
     app.insert_resource(Score(0))
+        .init_resource::<ApplesEaten>()
+        .init_resource::<ElapsedTime>()
         .add_systems(Startup, spawn_scoreboard)
-        .add_systems(Update, update_scoreboard);
+        .add_systems(Update, update_scoreboard)
+        .add_systems(
+            Update,
+            tick_elapsed_time.run_if(in_state(GameState::Playing)),
+        );
 }
 
-fn spawn_scoreboard(mut commands: Commands) {
+fn spawn_scoreboard(mut commands: Commands, config: Res<GameConfig>) {
+    let text_font = TextFont {
+        font_size: config.scoreboard.font_size,
+        ..default()
+    };
+    let text_color = TextColor(*config.scoreboard.text_color);
+
     commands
         .spawn((
             Text::new("Score: "),
-            TextFont {
-                font_size: SCOREBOARD_FONT_SIZE,
-                ..default()
-            },
-            TextColor(SCOREBOARD_TEXT_COLOR),
+            text_font.clone(),
+            text_color,
             ScoreboardUi,
             Node {
                 position_type: PositionType::Absolute,
-                top: SCOREBOARD_TEXT_TOP_PADDING,
-                left: SCOREBOARD_TEXT_LEFT_PADDING,
+                top: Val::Px(config.scoreboard.top_padding),
+                left: Val::Px(config.scoreboard.left_padding),
                 ..default()
             },
         ))
-        .with_child((
-            TextSpan::default(),
-            TextFont {
-                font_size: SCOREBOARD_FONT_SIZE,
-                ..default()
-            },
-            TextColor(SCOREBOARD_TEXT_COLOR),
-        ));
+        .with_children(|parent| {
+            parent.spawn((TextSpan::default(), text_font.clone(), text_color));
+            parent.spawn((TextSpan::new("  Speed: "), text_font.clone(), text_color));
+            parent.spawn((TextSpan::default(), text_font.clone(), text_color));
+            // Time attack clock, empty in the other modes
+            parent.spawn((TextSpan::default(), text_font, text_color));
+        });
 }
 
 fn update_scoreboard(
     score: Res<Score>,
+    speed: Res<SnakeSpeed>,
+    game_mode: Res<GameMode>,
+    time_left: Res<TimeLeft>,
     score_root: Single<Entity, (With<ScoreboardUi>, With<Text>)>,
     mut writer: TextUiWriter,
 ) {
     *writer.text(*score_root, 1) = score.to_string();
+    *writer.text(*score_root, 3) = speed.level().to_string();
+    *writer.text(*score_root, 4) = match *game_mode {
+        GameMode::TimeAttack => format!("  Time: {}", time_left.clock()),
+        _ => String::new(),
+    };
+}
+
+fn tick_elapsed_time(time: Res<Time>, mut elapsed_time: ResMut<ElapsedTime>) {
+    elapsed_time.tick(time.delta());
 }
@@ -1,146 +1,442 @@
-use bevy::prelude::*;
-use rand::Rng;
-
-use crate::data::constants::*;
-
-use super::score::Score;
+use std::collections::VecDeque;
+
+use bevy::{
+    ecs::{
+        component::Tick,
+        schedule::SystemConfigs,
+        system::{SystemChangeTick, SystemParam},
+    },
+    prelude::*,
+};
+
+use crate::data::args::LaunchArgs;
+use crate::data::config::GameConfig;
+use crate::simulation::board::{Board, HazardOutcome, StepOutcome};
+use crate::simulation::difficulty::{Difficulty, SpeedCurve};
+use crate::simulation::food::FoodKind;
+use crate::simulation::grid::{Direction, GridPos};
+use crate::utils::grid::ArenaGeometry;
+
+use super::food::{spawn_food, Food};
+use super::game_state::GameState;
+use super::level::CurrentLevel;
+use super::rng::GameRng;
+use super::score::{ApplesEaten, ElapsedTime, Score};
+use super::time_attack::TimeLeft;
+use super::wall::ArenaMode;
+
+// Everything spawned for a run, despawned when the run is over
+#[derive(Component, Default)]
+pub struct GameEntity;
 
 #[derive(Component, Clone)]
+#[require(GameEntity)]
 pub struct BodySnake {
-    direction: Vec3,
+    direction: Direction,
 }
 
 #[derive(Component)]
+#[require(GameEntity)]
 pub struct Snake {
-    direction: Vec3,
+    direction: Direction,
     tail: Vec<Entity>,
+    // Turns pressed by the player, applied one per movement tick
+    turns: VecDeque<Direction>,
+    max_turns: usize,
+}
+
+impl Snake {
+    fn new(direction: Direction, tail: Vec<Entity>, max_turns: usize) -> Self {
+        Self {
+            direction,
+            tail,
+            turns: VecDeque::with_capacity(max_turns),
+            max_turns,
+        }
+    }
+
+    /// Queues a turn behind the ones still pending. A turn that repeats or
+    /// reverses the heading the snake will have at that point is ignored.
+    fn queue_turn(&mut self, direction: Direction) {
+        let heading = self.turns.back().copied().unwrap_or(self.direction);
+
+        if direction == heading
+            || direction == heading.opposite()
+            || self.turns.len() >= self.max_turns
+        {
+            return;
+        }
+
+        self.turns.push_back(direction);
+    }
 }
 
+/// Enemy moving on its own tick, the index of its hazard on the board
 #[derive(Component)]
-pub struct Apple;
+#[require(GameEntity)]
+pub struct Enemy(usize);
+
+/// Time between two enemy movements, advanced by the `FixedUpdate`
+/// timestep so enemies keep in step with the snake whatever the frame rate
+#[derive(Resource, Deref, DerefMut)]
+struct HazardTimer(Timer);
+
+/// Score and timers of the run, started over by `reset_game`
+#[derive(SystemParam)]
+struct RunClocks<'w> {
+    score: ResMut<'w, Score>,
+    apples_eaten: ResMut<'w, ApplesEaten>,
+    elapsed_time: ResMut<'w, ElapsedTime>,
+    time_left: ResMut<'w, TimeLeft>,
+    hazard_timer: ResMut<'w, HazardTimer>,
+}
+
+/// When the current run was spawned. A level changed after it, by a hot
+/// reload, is rebuilt once the run is back in `Playing`.
+#[derive(Resource, Default)]
+struct RunSpawnTick(Tick);
 
+/// Snake movements per second, applied as the `FixedUpdate` timestep
+#[derive(Resource, Deref, DerefMut, PartialEq)]
+pub struct TickRate(f64);
+
+/// Difficulty of the next runs
+#[derive(Resource, Deref, DerefMut)]
+pub struct SelectedDifficulty(pub Difficulty);
+
+/// Speed of the current run, going up with the apples eaten
 #[derive(Resource)]
-struct MovementTimer(Timer);
+pub struct SnakeSpeed {
+    curve: SpeedCurve,
+    apples: usize,
+    // Speed factor of the last pepper or ice eaten, until the timer ends
+    effect: Option<(f64, Timer)>,
+}
 
-#[derive(Event, Default)]
-struct GetAppleEvent;
+impl SnakeSpeed {
+    fn new(difficulty: Difficulty) -> Self {
+        Self {
+            curve: difficulty.speed_curve(),
+            apples: 0,
+            effect: None,
+        }
+    }
+
+    pub fn level(&self) -> usize {
+        self.curve.level(self.apples)
+    }
+
+    fn tick_rate(&self) -> f64 {
+        let factor = self.effect.as_ref().map_or(1.0, |(factor, _)| *factor);
+
+        self.curve.rate(self.level()) * factor
+    }
+}
+
+#[derive(Resource, Deref, DerefMut)]
+pub struct SnakeBoard(Board);
+
+#[derive(Event)]
+pub struct GetFoodEvent {
+    pub cell: GridPos,
+    pub kind: FoodKind,
+}
 
 #[derive(Event, Default)]
 struct GameOverEvent;
 
 #[derive(Event, Default)]
-struct NewBodySnalePartEvent;
+pub struct NewBodySnalePartEvent;
+
+/// The snake covers every free cell of the arena
+#[derive(Event, Default)]
+struct BoardFullEvent;
 
-enum SnakeMovement {
-    Up,
-    Down,
-    Left,
-    Right,
+pub fn snake_plugin(app: &mut App) {
+    let snake_config = &app.world().resource::<GameConfig>().snake;
+    if snake_config.seconds_per_movement.is_some() {
+        warn!("snake.seconds_per_movement is no longer used, the snake speed follows snake.difficulty");
+    }
+
+    let difficulty = app
+        .world()
+        .resource::<LaunchArgs>()
+        .difficulty
+        .unwrap_or(snake_config.difficulty);
+    let speed = SnakeSpeed::new(difficulty);
+
+    let hazard_seconds = app
+        .world()
+        .resource::<GameConfig>()
+        .hazards
+        .seconds_per_movement;
+
+    app.insert_resource(TickRate(speed.tick_rate()))
+        .insert_resource(HazardTimer(Timer::from_seconds(
+            hazard_seconds,
+            TimerMode::Repeating,
+        )))
+        .insert_resource(speed)
+        .insert_resource(SelectedDifficulty(difficulty))
+        .init_resource::<RunSpawnTick>()
+        .add_event::<GetFoodEvent>()
+        .add_event::<GameOverEvent>()
+        .add_event::<NewBodySnalePartEvent>()
+        .add_event::<BoardFullEvent>()
+        .add_systems(OnEnter(GameState::Menu), despawn_game)
+        .add_systems(OnEnter(GameState::Editor), despawn_game)
+        .add_systems(
+            OnTransition {
+                exited: GameState::Editor,
+                entered: GameState::Playing,
+            },
+            new_run(),
+        )
+        .add_systems(
+            OnTransition {
+                exited: GameState::Menu,
+                entered: GameState::Playing,
+            },
+            new_run(),
+        )
+        .add_systems(
+            OnTransition {
+                exited: GameState::GameOver,
+                entered: GameState::Playing,
+            },
+            new_run(),
+        )
+        .add_systems(
+            OnTransition {
+                exited: GameState::Victory,
+                entered: GameState::Playing,
+            },
+            new_run(),
+        )
+        .add_systems(
+            OnTransition {
+                exited: GameState::TimeUp,
+                entered: GameState::Playing,
+            },
+            new_run(),
+        )
+        // Each campaign level starts a new run on the level picked meanwhile
+        .add_systems(
+            OnTransition {
+                exited: GameState::LevelTransition,
+                entered: GameState::Playing,
+            },
+            new_run(),
+        )
+        // Restart from the pause screen, resuming goes through `Resuming`
+        .add_systems(
+            OnTransition {
+                exited: GameState::Paused,
+                entered: GameState::Playing,
+            },
+            new_run(),
+        )
+        .add_systems(
+            Update,
+            (despawn_game, spawn_game, record_run_spawn)
+                .chain()
+                .run_if(in_state(GameState::Playing).and(level_changed_since_spawn)),
+        )
+        .add_systems(Update, apply_tick_rate.run_if(resource_changed::<TickRate>))
+        .add_systems(
+            FixedUpdate,
+            (snake_movement_tick, hazard_movement_tick)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            (
+                snake_movement,
+                listener_get_food,
+                listener_speed_up,
+                tick_speed_effect,
+                listener_game_over,
+                listener_new_body_snake_part,
+                listener_board_full,
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
 }
 
-impl SnakeMovement {
-    fn from_direction(direction: Vec3) -> Self {
-        match direction {
-            Vec3 {
-                x: 0.0,
-                y: 1.0,
-                z: 0.0,
-            } => SnakeMovement::Up,
-            Vec3 {
-                x: 0.0,
-                y: -1.0,
-                z: 0.0,
-            } => SnakeMovement::Down,
-            Vec3 {
-                x: -1.0,
-                y: 0.0,
-                z: 0.0,
-            } => SnakeMovement::Left,
-            Vec3 {
-                x: 1.0,
-                y: 0.0,
-                z: 0.0,
-            } => SnakeMovement::Right,
-            _ => panic!("Invalid direction"),
-        }
+/// Replaces whatever is left of the previous run with a new one
+fn new_run() -> SystemConfigs {
+    (despawn_game, reset_game, spawn_game, record_run_spawn).chain()
+}
+
+fn record_run_spawn(mut run_spawn_tick: ResMut<RunSpawnTick>, ticks: SystemChangeTick) {
+    run_spawn_tick.0 = ticks.this_run();
+}
+
+/// A level reloaded while paused or over is only rebuilt from here, so the
+/// score and the clocks of the run carry on
+fn level_changed_since_spawn(
+    level: Res<CurrentLevel>,
+    run_spawn_tick: Res<RunSpawnTick>,
+    ticks: SystemChangeTick,
+) -> bool {
+    level
+        .last_changed()
+        .is_newer_than(run_spawn_tick.0, ticks.this_run())
+}
+
+fn despawn_game(mut commands: Commands, game_entity_query: Query<Entity, With<GameEntity>>) {
+    for game_entity in &game_entity_query {
+        commands.entity(game_entity).despawn_recursive();
     }
+}
+
+fn reset_game(
+    mut clocks: RunClocks,
+    mut rng: ResMut<GameRng>,
+    difficulty: Res<SelectedDifficulty>,
+    mut speed: ResMut<SnakeSpeed>,
+    mut tick_rate: ResMut<TickRate>,
+    config: Res<GameConfig>,
+) {
+    **clocks.score = 0;
+    **clocks.apples_eaten = 0;
+    clocks.elapsed_time.reset();
+    clocks.time_left.reset(&config);
+    clocks.hazard_timer.reset();
+    rng.reset();
+    *speed = SnakeSpeed::new(**difficulty);
+    **tick_rate = speed.tick_rate();
+}
+
+fn listener_speed_up(
+    mut event_reader: EventReader<GetFoodEvent>,
+    mut speed: ResMut<SnakeSpeed>,
+    mut tick_rate: ResMut<TickRate>,
+    config: Res<GameConfig>,
+) {
+    for &GetFoodEvent { kind, .. } in event_reader.read() {
+        // Only apples count towards the speed level
+        if matches!(kind, FoodKind::Apple | FoodKind::Golden) {
+            speed.apples += 1;
+        }
 
-    fn to_direction(&self) -> Vec3 {
-        match self {
-            SnakeMovement::Up => Vec3::new(0.0, 1.0, 0.0),
-            SnakeMovement::Down => Vec3::new(0.0, -1.0, 0.0),
-            SnakeMovement::Left => Vec3::new(-1.0, 0.0, 0.0),
-            SnakeMovement::Right => Vec3::new(1.0, 0.0, 0.0),
+        if let Some(factor) = kind.speed_factor() {
+            let timer = Timer::from_seconds(config.food.speed_effect_seconds, TimerMode::Once);
+            speed.effect = Some((factor, timer));
         }
+
+        tick_rate.set_if_neq(TickRate(speed.tick_rate()));
     }
 }
 
-pub fn snake_plugin(app: &mut App) {
-    app.insert_resource(MovementTimer(Timer::from_seconds(
-        SNAKE_SECONDS_PER_MOVEMENT,
-        TimerMode::Repeating,
-    )))
-    .add_event::<GetAppleEvent>()
-    .add_event::<GameOverEvent>()
-    .add_event::<NewBodySnalePartEvent>()
-    .add_systems(Startup, spawn_game)
-    .add_systems(
-        Update,
-        (
-            snake_movement,
-            snake_movement_timer,
-            listener_get_apple,
-            listener_game_over,
-            listener_new_body_snake_part,
-        ),
-    );
+/// Ends the pepper or ice speed change once its time is up
+fn tick_speed_effect(
+    time: Res<Time>,
+    mut speed: ResMut<SnakeSpeed>,
+    mut tick_rate: ResMut<TickRate>,
+) {
+    let Some((_, timer)) = &mut speed.effect else {
+        return;
+    };
+
+    if timer.tick(time.delta()).just_finished() {
+        speed.effect = None;
+        tick_rate.set_if_neq(TickRate(speed.tick_rate()));
+    }
 }
 
-fn spawn_game(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let initial_tails = vec![commands
-        .spawn((
-            Sprite {
-                image: asset_server.load("sprites/snake-tail.png"),
-                custom_size: Some(Vec2::new(SNAKE_SIZE, SNAKE_SIZE)),
-                ..default()
-            },
-            Transform::from_xyz(-SNAKE_SIZE * 3.0 / 2.0, 0.0, 1.0),
-            BodySnake {
-                direction: SnakeMovement::Right.to_direction(),
-            },
-        ))
-        .id()];
+fn apply_tick_rate(tick_rate: Res<TickRate>, mut fixed_time: ResMut<Time<Fixed>>) {
+    fixed_time.set_timestep_hz(**tick_rate);
+}
+
+fn spawn_game(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+    level: Res<CurrentLevel>,
+    geometry: Res<ArenaGeometry>,
+    mut rng: ResMut<GameRng>,
+    arena_mode: Res<ArenaMode>,
+) {
+    let snake_size = Vec2::splat(geometry.cell_size);
+    let snake_cells = level.snake_cells();
+    let direction = level.snake_direction;
+
+    let mut board = Board::from_level(&level).with_wrap(*arena_mode == ArenaMode::Wrap);
+
+    let initial_tails = snake_cells
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, &cell)| {
+            let sprite = if i + 1 == snake_cells.len() {
+                "sprites/snake-tail.png"
+            } else {
+                "sprites/snake-body.png"
+            };
+
+            commands
+                .spawn((
+                    Sprite {
+                        image: asset_server.load(sprite),
+                        custom_size: Some(snake_size),
+                        ..default()
+                    },
+                    Transform {
+                        translation: geometry.cell_to_translation(cell, 1.0),
+                        rotation: direction.rotation(),
+                        ..default()
+                    },
+                    BodySnake { direction },
+                ))
+                .id()
+        })
+        .collect();
 
     commands.spawn((
         Sprite {
             image: asset_server.load("sprites/snake-head.png"),
-            custom_size: Some(Vec2::new(SNAKE_SIZE, SNAKE_SIZE)),
+            custom_size: Some(snake_size),
             ..default()
         },
-        Transform::from_xyz(-SNAKE_SIZE / 2.0, 0.0, 1.0),
-        Snake {
-            direction: SnakeMovement::Right.to_direction(),
-            tail: initial_tails,
+        Transform {
+            translation: geometry.cell_to_translation(snake_cells[0], 1.0),
+            rotation: direction.rotation(),
+            ..default()
         },
+        Snake::new(direction, initial_tails, config.snake.max_queued_turns),
     ));
 
-    let random_cell_x_apple: i32 = rand::thread_rng().gen_range(0..CELL_X_SIZE as i32);
-    let random_cell_y_apple: i32 = rand::thread_rng().gen_range(0..CELL_Y_SIZE as i32);
+    for (index, hazard) in board.hazards().iter().enumerate() {
+        commands.spawn((
+            Sprite {
+                color: *config.hazards.color,
+                custom_size: Some(Vec2::splat(geometry.cell_size * config.hazards.size)),
+                ..default()
+            },
+            // A square turned into a diamond, to tell it apart from walls
+            Transform {
+                translation: geometry.cell_to_translation(hazard.cell, 0.5),
+                rotation: Quat::from_rotation_z(std::f32::consts::FRAC_PI_4),
+                ..default()
+            },
+            Enemy(index),
+        ));
+    }
 
-    let start_apple_x = WALL_LEFT + WALL_WIDTH_OFFSET + WALL_THICKNESS + HALF_APPLE_SIZE - 10.0
-        + (random_cell_x_apple as f32) * APPLE_SIZE;
-    let start_apple_y = WALL_BOTTOM + WALL_HEIGHT_OFFSET + WALL_THICKNESS + HALF_APPLE_SIZE - 10.0
-        + (random_cell_y_apple as f32) * APPLE_SIZE;
+    for _ in 0..config.food.count {
+        spawn_food(
+            &mut commands,
+            &asset_server,
+            &config,
+            &geometry,
+            &mut board,
+            &mut **rng,
+        );
+    }
 
-    commands.spawn((
-        Sprite {
-            image: asset_server.load("sprites/apple.png"),
-            custom_size: Some(Vec2::new(APPLE_SIZE, APPLE_SIZE)),
-            ..default()
-        },
-        Transform::from_xyz(start_apple_x, start_apple_y, 0.0),
-        Apple {},
-    ));
+    commands.insert_resource(SnakeBoard(board));
 }
 
 fn snake_movement(
@@ -151,146 +447,166 @@ fn snake_movement(
         return;
     };
 
-    let mut direction: Vec3 = Vec3::ZERO;
+    let key_bindings = [
+        ([KeyCode::KeyW, KeyCode::ArrowUp], Direction::Up),
+        ([KeyCode::KeyS, KeyCode::ArrowDown], Direction::Down),
+        ([KeyCode::KeyA, KeyCode::ArrowLeft], Direction::Left),
+        ([KeyCode::KeyD, KeyCode::ArrowRight], Direction::Right),
+    ];
 
-    if keyboard_input.pressed(KeyCode::KeyW) || keyboard_input.pressed(KeyCode::ArrowUp) {
-        direction = Vec3::new(0.0, 1.0, 0.0);
-    }
-    if keyboard_input.pressed(KeyCode::KeyS) || keyboard_input.pressed(KeyCode::ArrowDown) {
-        direction = Vec3::new(0.0, -1.0, 0.0);
-    }
-    if keyboard_input.pressed(KeyCode::KeyA) || keyboard_input.pressed(KeyCode::ArrowLeft) {
-        direction = Vec3::new(-1.0, 0.0, 0.0);
-    }
-    if keyboard_input.pressed(KeyCode::KeyD) || keyboard_input.pressed(KeyCode::ArrowRight) {
-        direction = Vec3::new(1.0, 0.0, 0.0);
-    }
-
-    if direction.length() > 0.0 {
-        snake.direction = direction.normalize();
+    for (keys, direction) in key_bindings {
+        if keyboard_input.any_just_pressed(keys) {
+            snake.queue_turn(direction);
+        }
     }
 }
 
-fn snake_movement_timer(
-    time: Res<Time>,
-    mut timer: ResMut<MovementTimer>,
-    mut get_apple_event_writer: EventWriter<GetAppleEvent>,
+#[allow(clippy::too_many_arguments)]
+fn snake_movement_tick(
+    mut commands: Commands,
+    mut board: ResMut<SnakeBoard>,
+    mut get_food_event_writer: EventWriter<GetFoodEvent>,
     mut game_over_event_writer: EventWriter<GameOverEvent>,
-    mut snake_query: Query<(&mut Transform, &Snake), With<Snake>>,
+    mut snake_query: Query<(&mut Transform, &mut Snake), With<Snake>>,
     mut body_snake_query: Query<
         (&mut Transform, &mut BodySnake, &mut Sprite),
         (With<BodySnake>, Without<Snake>),
     >,
-    apple_query: Query<&Transform, (With<Apple>, Without<Snake>, Without<BodySnake>)>,
     asset_server: Res<AssetServer>,
+    geometry: Res<ArenaGeometry>,
 ) {
-    if !timer.0.tick(time.delta()).finished() {
+    let Ok((mut snake_transform, mut snake)) = snake_query.get_single_mut() else {
         return;
+    };
+
+    if let Some(direction) = snake.turns.pop_front() {
+        snake.direction = direction;
     }
 
-    let Ok((mut snake_transform, snake)) = snake_query.get_single_mut() else {
-        return;
-    };
+    match board.step(snake.direction) {
+        StepOutcome::Died(_) => {
+            game_over_event_writer.send_default();
+            return;
+        }
+        StepOutcome::Ate(kind) => {
+            get_food_event_writer.send(GetFoodEvent {
+                cell: board.head(),
+                kind,
+            });
+        }
+        StepOutcome::Moved => {}
+    }
 
-    let Ok(apple_transform) = apple_query.get_single() else {
-        return;
-    };
+    snake_transform.translation = geometry.cell_to_translation(board.head(), 1.0);
+    snake_transform.rotation = snake.direction.rotation();
 
-    let mut last_tail_position = snake_transform.translation;
-    let mut last_direction = snake.direction;
-    snake_transform.translation += snake.direction * SNAKE_SPEED;
-    snake_transform.rotation = match SnakeMovement::from_direction(snake.direction) {
-        SnakeMovement::Up => Quat::from_rotation_z(std::f32::consts::PI / 2.0),
-        SnakeMovement::Down => Quat::from_rotation_z(std::f32::consts::PI * 3.0 / 2.0),
-        SnakeMovement::Left => Quat::from_rotation_z(std::f32::consts::PI),
-        SnakeMovement::Right => Quat::from_rotation_z(0.0),
-    };
+    // Rotten food shrank the board snake, drop the segments it lost
+    let tail_len = board.tail().count();
+    if snake.tail.len() > tail_len {
+        for body_entity in snake.tail.drain(tail_len..) {
+            commands.entity(body_entity).despawn();
+        }
+    }
 
     // logic to move the snake tail
-    for (i, &body_entity) in snake.tail.iter().enumerate() {
+    let mut last_direction = snake.direction;
+    for (i, (&body_entity, body_cell)) in snake.tail.iter().zip(board.tail()).enumerate() {
         let Ok((mut body_snake_transform, mut body_snake, mut body_snake_sprite)) =
             body_snake_query.get_mut(body_entity)
         else {
             continue;
         };
 
-        // logic to move the snake tail
-        let aux_translation = body_snake_transform.translation;
-        body_snake_transform.translation = last_tail_position;
-        last_tail_position = aux_translation;
-
-        // The tail is going to move to a new direction
-        if body_snake.direction != last_direction {
-            let aux_direction = body_snake.direction;
-            body_snake.direction = last_direction;
-            last_direction = aux_direction;
-
-            let body_snake_direction = SnakeMovement::from_direction(body_snake.direction);
-
-            let rotation_angle: f32;
-
-            let first_right_movement_validation = body_snake.direction.x != 0.0
-                && last_direction.y != 0.0
-                && body_snake.direction.x == last_direction.y;
-
-            let second_right_movement_validation = body_snake.direction.y != 0.0
-                && last_direction.x != 0.0
-                && body_snake.direction.y != last_direction.x;
-
-            let third_right_movement_validation = i == snake.tail.len() - 1;
-
-            if first_right_movement_validation
-                || second_right_movement_validation
-                || third_right_movement_validation
-            {
-                // Movement to the Right
-                rotation_angle = match body_snake_direction {
-                    SnakeMovement::Up => std::f32::consts::PI / 2.0,
-                    SnakeMovement::Down => std::f32::consts::PI * 3.0 / 2.0,
-                    SnakeMovement::Left => std::f32::consts::PI,
-                    SnakeMovement::Right => 0.0,
-                };
-            } else {
-                //  Movement to the Left
-                rotation_angle = match body_snake_direction {
-                    SnakeMovement::Up => std::f32::consts::PI,
-                    SnakeMovement::Down => 0.0,
-                    SnakeMovement::Left => std::f32::consts::PI * 3.0 / 2.0,
-                    SnakeMovement::Right => std::f32::consts::PI / 2.0,
-                };
-            }
+        body_snake_transform.translation = geometry.cell_to_translation(body_cell, 1.0);
 
-            body_snake_transform.rotation = Quat::from_rotation_z(rotation_angle);
+        // The segment enters its new cell the way it was heading before and
+        // leaves it the way the segment ahead was heading. Sprites follow
+        // these headings rather than the cell positions, so segments split
+        // by a portal or a wrapped edge turn the same as adjacent ones.
+        let entry_direction = std::mem::replace(&mut body_snake.direction, last_direction);
+        let exit_direction = body_snake.direction;
+        last_direction = entry_direction;
 
-            if i != snake.tail.len() - 1 {
-                body_snake_sprite.image = asset_server.load("sprites/snake-corner.png");
-            }
-        } else if i != snake.tail.len() - 1 {
+        if i + 1 == snake.tail.len() {
+            body_snake_transform.rotation = exit_direction.rotation();
+            body_snake_sprite.image = asset_server.load("sprites/snake-tail.png");
+        } else if entry_direction == exit_direction {
             // Snake is going to move straight
-            let body_snake_direction = SnakeMovement::from_direction(body_snake.direction);
-            let rotation_angle = match body_snake_direction {
-                SnakeMovement::Up => std::f32::consts::PI / 2.0,
-                SnakeMovement::Down => std::f32::consts::PI * 3.0 / 2.0,
-                SnakeMovement::Left => std::f32::consts::PI,
-                SnakeMovement::Right => 0.0,
-            };
-            body_snake_transform.rotation = Quat::from_rotation_z(rotation_angle);
+            body_snake_transform.rotation = exit_direction.rotation();
             body_snake_sprite.image = asset_server.load("sprites/snake-body.png");
+        } else {
+            // The tail is going to move to a new direction
+            body_snake_transform.rotation = if exit_direction == entry_direction.turn_right() {
+                exit_direction.rotation()
+            } else {
+                exit_direction.turn_left().rotation()
+            };
+            body_snake_sprite.image = asset_server.load("sprites/snake-corner.png");
         }
     }
+}
+
+/// Moves the enemies, which kill the snake when they land on its head and
+/// cut its tail when they land on it
+#[allow(clippy::too_many_arguments)]
+fn hazard_movement_tick(
+    mut commands: Commands,
+    time: Res<Time<Fixed>>,
+    mut hazard_timer: ResMut<HazardTimer>,
+    mut board: ResMut<SnakeBoard>,
+    mut score: ResMut<Score>,
+    mut game_over_event_writer: EventWriter<GameOverEvent>,
+    mut enemy_query: Query<(&Enemy, &mut Transform), Without<BodySnake>>,
+    mut snake_query: Query<&mut Snake>,
+    mut body_snake_query: Query<(&mut Transform, &BodySnake, &mut Sprite)>,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+    geometry: Res<ArenaGeometry>,
+) {
+    // A slow snake tick can hold several enemy movements
+    let steps = hazard_timer.tick(time.delta()).times_finished_this_tick();
+    if board.hazards().is_empty() || steps == 0 {
+        return;
+    }
+
+    for _ in 0..steps {
+        match board.step_hazards() {
+            HazardOutcome::Died => {
+                game_over_event_writer.send_default();
+                break;
+            }
+            HazardOutcome::CutTail(segments) => {
+                **score = score.saturating_sub(segments * config.hazards.score_penalty);
+
+                let Ok(mut snake) = snake_query.get_single_mut() else {
+                    continue;
+                };
 
-    // logic to get apple
-    if (snake_transform.translation - apple_transform.translation).length() < SNAKE_SIZE {
-        get_apple_event_writer.send_default();
+                let tail_len = board.tail().count();
+                if snake.tail.len() > tail_len {
+                    for body_entity in snake.tail.drain(tail_len..) {
+                        commands.entity(body_entity).despawn();
+                    }
+                }
+
+                // The segment in front of the cut becomes the tip
+                if let Some(&last_tail_entity) = snake.tail.last() {
+                    if let Ok((mut transform, body_snake, mut sprite)) =
+                        body_snake_query.get_mut(last_tail_entity)
+                    {
+                        transform.rotation = body_snake.direction.rotation();
+                        sprite.image = asset_server.load("sprites/snake-tail.png");
+                    }
+                }
+            }
+            HazardOutcome::Moved => {}
+        }
     }
 
-    // logic to check if snake is out of bounds
-    if snake_transform.translation.x > SNAKE_X_MAX
-        || snake_transform.translation.x < SNAKE_X_MIN
-        || snake_transform.translation.y > SNAKE_Y_MAX
-        || snake_transform.translation.y < SNAKE_Y_MIN
-    {
-        game_over_event_writer.send_default();
+    for (&Enemy(index), mut transform) in &mut enemy_query {
+        if let Some(hazard) = board.hazards().get(index) {
+            transform.translation = geometry.cell_to_translation(hazard.cell, 0.5);
+        }
     }
 }
 
@@ -298,59 +614,90 @@ fn listener_game_over(
     mut commands: Commands,
     mut event_reader: EventReader<GameOverEvent>,
     asset_server: Res<AssetServer>,
-    snake_query: Query<Entity, With<Snake>>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
-    for _ in event_reader.read() {
-        let Ok(snake_entity) = snake_query.get_single() else {
-            return;
-        };
-
-        println!("Game Over");
-        let sound_effect = asset_server.load("audio/explosionCrunch_000.ogg");
-        commands.spawn(AudioPlayer::new(sound_effect));
-        commands.entity(snake_entity).despawn_recursive();
+    if event_reader.read().count() == 0 {
+        return;
     }
+
+    let sound_effect = asset_server.load("audio/explosionCrunch_000.ogg");
+    commands.spawn(AudioPlayer::new(sound_effect));
+    next_state.set(GameState::GameOver);
 }
 
-fn listener_get_apple(
+#[allow(clippy::too_many_arguments)]
+pub fn listener_get_food(
     mut commands: Commands,
     mut score: ResMut<Score>,
-    mut event_reader: EventReader<GetAppleEvent>,
+    mut apples_eaten: ResMut<ApplesEaten>,
+    mut event_reader: EventReader<GetFoodEvent>,
+    mut board: ResMut<SnakeBoard>,
+    mut rng: ResMut<GameRng>,
     asset_server: Res<AssetServer>,
-    mut apple_query: Query<&mut Transform, With<Apple>>,
+    config: Res<GameConfig>,
+    geometry: Res<ArenaGeometry>,
+    food_query: Query<(Entity, &Food)>,
     mut new_body_snake_part_event_writer: EventWriter<NewBodySnalePartEvent>,
 ) {
-    for _ in event_reader.read() {
-        let Ok(mut apple_transform) = apple_query.get_single_mut() else {
-            return;
+    for &GetFoodEvent { cell, .. } in event_reader.read() {
+        let Some((food_entity, food)) = food_query.iter().find(|(_, food)| food.cell == cell)
+        else {
+            continue;
         };
+        let kind = food.kind;
 
-        **score += 1;
+        commands.entity(food_entity).despawn_recursive();
 
-        let sound_effect = asset_server.load("audio/coin_000.ogg");
+        **score += kind.points();
+        if matches!(kind, FoodKind::Apple | FoodKind::Golden) {
+            **apples_eaten += 1;
+        }
+
+        let sound_effect = asset_server.load(config.food.kind(kind).sound.clone());
         commands.spawn(AudioPlayer::new(sound_effect));
 
-        // Reubicar la manzana
-        let random_cell_x_apple: i32 = rand::thread_rng().gen_range(0..CELL_X_SIZE as i32);
-        let random_cell_y_apple: i32 = rand::thread_rng().gen_range(0..CELL_Y_SIZE as i32);
+        if kind.growth() > 0 {
+            new_body_snake_part_event_writer.send_default();
+        }
 
-        let start_apple_x = WALL_LEFT + WALL_WIDTH_OFFSET + WALL_THICKNESS + HALF_APPLE_SIZE - 10.0
-            + (random_cell_x_apple as f32) * APPLE_SIZE;
-        let start_apple_y = WALL_BOTTOM + WALL_HEIGHT_OFFSET + WALL_THICKNESS + HALF_APPLE_SIZE
-            - 10.0
-            + (random_cell_y_apple as f32) * APPLE_SIZE;
+        // The next food, none once the snake covers the whole arena
+        spawn_food(
+            &mut commands,
+            &asset_server,
+            &config,
+            &geometry,
+            &mut board,
+            &mut **rng,
+        );
+    }
+}
 
-        apple_transform.translation = Vec3::new(start_apple_x, start_apple_y, 0.0);
-        new_body_snake_part_event_writer.send_default();
+/// No food can be placed on a full board, which wins the run
+fn listener_board_full(
+    mut commands: Commands,
+    mut event_reader: EventReader<BoardFullEvent>,
+    asset_server: Res<AssetServer>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if event_reader.read().count() == 0 {
+        return;
     }
+
+    let sound_effect = asset_server.load("audio/coin_000.ogg");
+    commands.spawn(AudioPlayer::new(sound_effect));
+    next_state.set(GameState::Victory);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn listener_new_body_snake_part(
     mut commands: Commands,
     mut event_reader: EventReader<NewBodySnalePartEvent>,
     mut snake_query: Query<&mut Snake, With<Snake>>,
     mut body_snake: Query<(&Transform, &mut Sprite, &mut BodySnake)>,
+    board: Res<SnakeBoard>,
     asset_server: Res<AssetServer>,
+    geometry: Res<ArenaGeometry>,
+    mut board_full_event_writer: EventWriter<BoardFullEvent>,
 ) {
     for _ in event_reader.read() {
         let Ok(mut snake) = snake_query.get_single_mut() else {
@@ -366,10 +713,15 @@ fn listener_new_body_snake_part(
 
             last_tail_sprite.image = asset_server.load("sprites/snake-body.png");
 
-            let last_position = last_tail_transform.translation;
+            // The board already grew, the new segment takes the first cell
+            // of the tail that has no entity yet
+            let Some(new_cell) = board.tail().nth(snake.tail.len()) else {
+                continue;
+            };
+
             let last_direction = last_tail_body.direction;
 
-            let new_position = last_position - last_direction * SNAKE_SIZE;
+            let new_position = geometry.cell_to_translation(new_cell, 1.0);
             let new_rotation = last_tail_transform.rotation;
 
             snake.tail.push(
@@ -377,7 +729,7 @@ fn listener_new_body_snake_part(
                     .spawn((
                         Sprite {
                             image: asset_server.load("sprites/snake-tail.png"),
-                            custom_size: Some(Vec2::new(SNAKE_SIZE, SNAKE_SIZE)),
+                            custom_size: Some(Vec2::splat(geometry.cell_size)),
                             ..default()
                         },
                         Transform {
@@ -392,5 +744,42 @@ fn listener_new_body_snake_part(
                     .id(),
             );
         }
+
+        // logic to check if the snake fills the whole arena
+        if board.is_full() {
+            board_full_event_writer.send_default();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue_turn_ignores_repeated_and_reversed_headings() {
+        let mut snake = Snake::new(Direction::Right, Vec::new(), 3);
+
+        snake.queue_turn(Direction::Right);
+        snake.queue_turn(Direction::Left);
+        assert!(snake.turns.is_empty());
+
+        // Checked against the heading after the turns already queued
+        snake.queue_turn(Direction::Up);
+        snake.queue_turn(Direction::Down);
+        snake.queue_turn(Direction::Up);
+        snake.queue_turn(Direction::Left);
+        assert_eq!(snake.turns, [Direction::Up, Direction::Left]);
+    }
+
+    #[test]
+    fn queue_turn_keeps_at_most_max_turns() {
+        let mut snake = Snake::new(Direction::Right, Vec::new(), 2);
+
+        for direction in [Direction::Up, Direction::Left, Direction::Down] {
+            snake.queue_turn(direction);
+        }
+
+        assert_eq!(snake.turns, [Direction::Up, Direction::Left]);
     }
 }
@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+
+use crate::data::config::GameConfig;
+use crate::simulation::food::FoodKind;
+
+use super::game_state::{GameMode, GameState};
+use super::snake::GetFoodEvent;
+
+/// Seconds left on the time attack clock
+#[derive(Resource, Deref, DerefMut)]
+pub struct TimeLeft(f32);
+
+#[derive(Event, Default)]
+struct TimeUpEvent;
+
+pub fn time_attack_plugin(app: &mut App) {
+    let seconds = app.world().resource::<GameConfig>().time_attack.seconds;
+
+    app.insert_resource(TimeLeft(seconds))
+        .add_event::<TimeUpEvent>()
+        .add_systems(
+            Update,
+            (listener_bonus_time, tick_time_left, listener_time_up)
+                .chain()
+                .run_if(in_state(GameState::Playing).and(resource_equals(GameMode::TimeAttack))),
+        );
+}
+
+impl TimeLeft {
+    /// The clock as minutes and seconds, rounded up so it reads 00:00 only
+    /// once the time is up
+    pub fn clock(&self) -> String {
+        let seconds = self.0.ceil() as u32;
+
+        format!("{:02}:{:02}", seconds / 60, seconds % 60)
+    }
+
+    pub fn reset(&mut self, config: &GameConfig) {
+        self.0 = config.time_attack.seconds;
+    }
+}
+
+fn listener_bonus_time(
+    mut event_reader: EventReader<GetFoodEvent>,
+    mut time_left: ResMut<TimeLeft>,
+    config: Res<GameConfig>,
+) {
+    for event in event_reader.read() {
+        if matches!(event.kind, FoodKind::Apple | FoodKind::Golden) {
+            **time_left += config.time_attack.apple_bonus_seconds;
+        }
+    }
+}
+
+fn tick_time_left(
+    time: Res<Time>,
+    mut time_left: ResMut<TimeLeft>,
+    mut time_up_event_writer: EventWriter<TimeUpEvent>,
+) {
+    **time_left = (**time_left - time.delta_secs()).max(0.0);
+
+    if **time_left == 0.0 {
+        time_up_event_writer.send_default();
+    }
+}
+
+fn listener_time_up(
+    mut commands: Commands,
+    mut event_reader: EventReader<TimeUpEvent>,
+    asset_server: Res<AssetServer>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if event_reader.read().count() == 0 {
+        return;
+    }
+
+    let sound_effect = asset_server.load("audio/pluck_002.ogg");
+    commands.spawn(AudioPlayer::new(sound_effect));
+    next_state.set(GameState::TimeUp);
+}
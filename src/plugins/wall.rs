@@ -1,7 +1,11 @@
 use bevy::prelude::*;
 
-use crate::data::colors::WALL_COLOR;
-use crate::data::constants::*;
+use crate::data::args::LaunchArgs;
+use crate::data::config::{ArenaConfig, GameConfig};
+use crate::simulation::grid::GridPos;
+use crate::utils::grid::ArenaGeometry;
+
+use super::level::CurrentLevel;
 
 // Default must be implemented to define this as a required component for the Wall component below
 #[derive(Component, Default)]
@@ -11,6 +15,26 @@ struct Collider;
 #[require(Sprite, Transform, Collider)]
 pub struct Wall;
 
+/// How the snake treats the border walls
+#[derive(Resource, Clone, Copy, PartialEq, Eq)]
+pub enum ArenaMode {
+    // Touching a wall ends the game
+    Walls,
+    // Leaving through one edge comes back through the opposite one
+    Wrap,
+}
+
+impl ArenaMode {
+    /// Reads the mode from the `--wrap` flag
+    fn from_args(launch_args: &LaunchArgs) -> Self {
+        if launch_args.wrap {
+            ArenaMode::Wrap
+        } else {
+            ArenaMode::Walls
+        }
+    }
+}
+
 enum WallLocation {
     Top,
     Bottom,
@@ -19,54 +43,137 @@ enum WallLocation {
 }
 
 impl WallLocation {
-    fn position(&self) -> Vec2 {
-        match self {
-            WallLocation::Top => Vec2::new(WALL_WIDTH_OFFSET, WALL_TOP + WALL_HEIGHT_OFFSET),
-            WallLocation::Bottom => Vec2::new(WALL_WIDTH_OFFSET, WALL_BOTTOM + WALL_HEIGHT_OFFSET),
-            WallLocation::Left => Vec2::new(WALL_LEFT + WALL_WIDTH_OFFSET, WALL_HEIGHT_OFFSET),
-            WallLocation::Right => Vec2::new(WALL_RIGHT + WALL_WIDTH_OFFSET, WALL_HEIGHT_OFFSET),
-        }
-    }
+    fn position(&self, geometry: &ArenaGeometry) -> Vec2 {
+        let offset = (geometry.inner_size() + geometry.wall_thickness) / 2.0;
 
-    fn size(&self) -> Vec2 {
-        let arena_height = WALL_TOP - WALL_BOTTOM;
-        let arena_width = WALL_RIGHT - WALL_LEFT;
+        geometry.center
+            + match self {
+                WallLocation::Top => Vec2::new(0.0, offset.y),
+                WallLocation::Bottom => Vec2::new(0.0, -offset.y),
+                WallLocation::Left => Vec2::new(-offset.x, 0.0),
+                WallLocation::Right => Vec2::new(offset.x, 0.0),
+            }
+    }
 
-        assert!(arena_height > 0.0);
-        assert!(arena_width > 0.0);
+    fn size(&self, geometry: &ArenaGeometry) -> Vec2 {
+        let outer_size = geometry.inner_size() + geometry.wall_thickness * 2.0;
 
         match self {
             WallLocation::Top | WallLocation::Bottom => {
-                Vec2::new(arena_width + WALL_THICKNESS, WALL_THICKNESS)
+                Vec2::new(outer_size.x, geometry.wall_thickness)
             }
             WallLocation::Left | WallLocation::Right => {
-                Vec2::new(WALL_THICKNESS, arena_height + WALL_THICKNESS)
+                Vec2::new(geometry.wall_thickness, outer_size.y)
             }
         }
     }
 }
 
 impl Wall {
-    fn new(location: WallLocation) -> (Wall, Sprite, Transform) {
+    fn new(
+        location: WallLocation,
+        geometry: &ArenaGeometry,
+        arena: &ArenaConfig,
+    ) -> (Wall, Sprite, Transform) {
+        Wall::from_rect(location.position(geometry), location.size(geometry), arena)
+    }
+
+    /// The wall split into dashes, showing that the snake can pass through
+    fn dashed(
+        location: WallLocation,
+        geometry: &ArenaGeometry,
+        arena: &ArenaConfig,
+    ) -> Vec<(Wall, Sprite, Transform)> {
+        let position = location.position(geometry);
+        let size = location.size(geometry);
+        let dash_length = arena.wall_dash_length;
+
+        let along = if size.x > size.y { Vec2::X } else { Vec2::Y };
+        let length = size.dot(along);
+        let dash_size = size - along * (length - dash_length);
+
+        // Dashes and gaps share the same length, centered on the wall
+        let dash_count = ((length + dash_length) / (dash_length * 2.0)) as i32;
+        let dashes_span = (dash_count * 2 - 1) as f32 * dash_length;
+        let first_dash = position - along * (dashes_span - dash_length) / 2.0;
+
+        (0..dash_count)
+            .map(|i| {
+                Wall::from_rect(
+                    first_dash + along * (i as f32) * dash_length * 2.0,
+                    dash_size,
+                    arena,
+                )
+            })
+            .collect()
+    }
+
+    /// A wall filling a single cell inside the arena
+    fn cell(
+        cell: GridPos,
+        geometry: &ArenaGeometry,
+        arena: &ArenaConfig,
+    ) -> (Wall, Sprite, Transform) {
+        Wall::from_rect(
+            geometry.cell_to_translation(cell, 0.0).truncate(),
+            Vec2::splat(geometry.cell_size),
+            arena,
+        )
+    }
+
+    fn from_rect(position: Vec2, size: Vec2, arena: &ArenaConfig) -> (Wall, Sprite, Transform) {
         (
             Wall,
-            Sprite::from_color(WALL_COLOR, Vec2::ONE),
+            Sprite::from_color(*arena.wall_color, Vec2::ONE),
             Transform {
-                translation: location.position().extend(0.0),
-                scale: location.size().extend(1.0),
+                translation: position.extend(0.0),
+                scale: size.extend(1.0),
                 ..default()
             },
         )
     }
 }
 
-fn init_walls(mut commands: Commands) {
-    commands.spawn(Wall::new(WallLocation::Top));
-    commands.spawn(Wall::new(WallLocation::Bottom));
-    commands.spawn(Wall::new(WallLocation::Left));
-    commands.spawn(Wall::new(WallLocation::Right));
+fn init_walls(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    geometry: Res<ArenaGeometry>,
+    level: Res<CurrentLevel>,
+    arena_mode: Res<ArenaMode>,
+    wall_query: Query<Entity, With<Wall>>,
+) {
+    for wall_entity in &wall_query {
+        commands.entity(wall_entity).despawn();
+    }
+
+    let locations = [
+        WallLocation::Top,
+        WallLocation::Bottom,
+        WallLocation::Left,
+        WallLocation::Right,
+    ];
+
+    for location in locations {
+        match *arena_mode {
+            ArenaMode::Walls => {
+                commands.spawn(Wall::new(location, &geometry, &config.arena));
+            }
+            ArenaMode::Wrap => {
+                commands.spawn_batch(Wall::dashed(location, &geometry, &config.arena));
+            }
+        }
+    }
+
+    for &cell in &level.walls {
+        commands.spawn(Wall::cell(cell, &geometry, &config.arena));
+    }
 }
 
 pub fn wall_plugin(app: &mut App) {
-    app.add_systems(Startup, init_walls);
+    let arena_mode = ArenaMode::from_args(app.world().resource::<LaunchArgs>());
+
+    app.insert_resource(arena_mode).add_systems(
+        Update,
+        init_walls.run_if(resource_changed::<CurrentLevel>.or(resource_changed::<ArenaMode>)),
+    );
 }
@@ -0,0 +1,546 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use super::food::FoodKind;
+use super::grid::{Direction, GridPos};
+use super::hazard::Hazard;
+use super::level::Level;
+
+/// Why the snake died during a step
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeathCause {
+    Wall,
+    Tail,
+    Hazard,
+}
+
+/// What happened to the snake during a step
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepOutcome {
+    Moved,
+    Ate(FoodKind),
+    Died(DeathCause),
+}
+
+/// What the enemies did to the snake during their step
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HazardOutcome {
+    Moved,
+    // Segments lost from the tail
+    CutTail(usize),
+    Died,
+}
+
+/// Game rules on a grid of cells, without any knowledge of Bevy
+pub struct Board {
+    width: i32,
+    height: i32,
+    // Head first, tail tip last
+    snake: VecDeque<GridPos>,
+    food: HashMap<GridPos, FoodKind>,
+    walls: HashSet<GridPos>,
+    // Fixed food cells visited in order, random free cells when empty
+    food_cells: Vec<GridPos>,
+    next_food_cell: usize,
+    // Leaving through one edge comes back through the opposite one
+    wrap: bool,
+    // Both ends of each portal pair, mapped to the other end
+    portals: HashMap<GridPos, GridPos>,
+    hazards: Vec<Hazard>,
+}
+
+impl Board {
+    pub fn new(width: i32, height: i32, snake: impl IntoIterator<Item = GridPos>) -> Self {
+        let snake: VecDeque<GridPos> = snake.into_iter().collect();
+
+        assert!(width > 0 && height > 0);
+        assert!(!snake.is_empty());
+
+        Self {
+            width,
+            height,
+            snake,
+            food: HashMap::new(),
+            walls: HashSet::new(),
+            food_cells: Vec::new(),
+            next_food_cell: 0,
+            wrap: false,
+            portals: HashMap::new(),
+            hazards: Vec::new(),
+        }
+    }
+
+    pub fn from_level(level: &Level) -> Self {
+        Self {
+            walls: level.walls.clone(),
+            food_cells: level.apples.clone(),
+            portals: level
+                .portals
+                .iter()
+                .flat_map(|&(entry, exit)| [(entry, exit), (exit, entry)])
+                .collect(),
+            hazards: level.hazards.clone(),
+            ..Board::new(level.width, level.height, level.snake_cells())
+        }
+    }
+
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn head(&self) -> GridPos {
+        self.snake[0]
+    }
+
+    /// Cells of the tail, from the neck to the tail tip
+    pub fn tail(&self) -> impl Iterator<Item = GridPos> + '_ {
+        self.snake.iter().skip(1).copied()
+    }
+
+    /// Cells covered neither by the snake, a wall nor a portal
+    pub fn free_cells(&self) -> Vec<GridPos> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| GridPos::new(x, y)))
+            .filter(|cell| {
+                !self.snake.contains(cell)
+                    && !self.walls.contains(cell)
+                    && !self.portals.contains_key(cell)
+            })
+            .collect()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.free_cells().is_empty()
+    }
+
+    /// Adds food on the next fixed food cell neither the snake nor other
+    /// food covers, or else on a random free cell. Returns `None`, adding
+    /// nothing, when no such cell is left.
+    pub fn place_food(&mut self, kind: FoodKind, rng: &mut impl Rng) -> Option<GridPos> {
+        let is_taken = |cell: &GridPos| self.snake.contains(cell) || self.food.contains_key(cell);
+
+        let fixed_count = self.food_cells.len();
+        let next_fixed = (0..fixed_count)
+            .map(|i| (self.next_food_cell + i) % fixed_count)
+            .find(|&i| !is_taken(&self.food_cells[i]));
+
+        let cell = match next_fixed {
+            Some(i) => {
+                self.next_food_cell = (i + 1) % fixed_count;
+                self.food_cells[i]
+            }
+            None => {
+                let free_cells: Vec<GridPos> = self
+                    .free_cells()
+                    .into_iter()
+                    .filter(|cell| !is_taken(cell))
+                    .collect();
+                *free_cells.choose(rng)?
+            }
+        };
+
+        self.food.insert(cell, kind);
+        Some(cell)
+    }
+
+    pub fn has_food(&self, cell: GridPos) -> bool {
+        self.food.contains_key(&cell)
+    }
+
+    pub fn remove_food(&mut self, cell: GridPos) {
+        self.food.remove(&cell);
+    }
+
+    pub fn hazards(&self) -> &[Hazard] {
+        &self.hazards
+    }
+
+    pub fn in_bounds(&self, cell: GridPos) -> bool {
+        (0..self.width).contains(&cell.x) && (0..self.height).contains(&cell.y)
+    }
+
+    /// Moves the head one cell towards `direction`, or out of the paired
+    /// portal with the same heading when it enters one. The tail tip leaves its
+    /// cell before the head enters the new one, then eaten food grows or
+    /// shrinks the snake, down to a head and one tail segment. A dead snake
+    /// is left untouched.
+    pub fn step(&mut self, direction: Direction) -> StepOutcome {
+        let mut next_head = self.head() + direction.delta();
+
+        if self.wrap {
+            next_head = GridPos::new(
+                next_head.x.rem_euclid(self.width),
+                next_head.y.rem_euclid(self.height),
+            );
+        }
+
+        if !self.in_bounds(next_head) || self.walls.contains(&next_head) {
+            return StepOutcome::Died(DeathCause::Wall);
+        }
+
+        if let Some(&exit) = self.portals.get(&next_head) {
+            next_head = exit;
+        }
+
+        if self.hazards.iter().any(|hazard| hazard.cell == next_head) {
+            return StepOutcome::Died(DeathCause::Hazard);
+        }
+
+        let eaten = self.food.get(&next_head).copied();
+
+        let tail_tip = self.snake.pop_back().unwrap();
+        if self.snake.contains(&next_head) {
+            self.snake.push_back(tail_tip);
+            return StepOutcome::Died(DeathCause::Tail);
+        }
+
+        self.snake.push_front(next_head);
+
+        if let Some(kind) = eaten {
+            if kind.growth() > 0 {
+                self.snake.push_back(tail_tip);
+            }
+
+            let shrink = (-kind.growth()).max(0) as usize;
+            let new_len = self.snake.len().saturating_sub(shrink).max(2);
+            self.snake.truncate(new_len);
+
+            self.food.remove(&next_head);
+            return StepOutcome::Ate(kind);
+        }
+
+        StepOutcome::Moved
+    }
+
+    /// Moves every enemy one cell. An enemy landing on the head kills the
+    /// snake, one landing on the tail cuts it there, keeping at least the
+    /// segment behind the head.
+    pub fn step_hazards(&mut self) -> HazardOutcome {
+        let mut outcome = HazardOutcome::Moved;
+
+        for i in 0..self.hazards.len() {
+            let (width, height, walls) = (self.width, self.height, &self.walls);
+            self.hazards[i].advance(|cell| {
+                !(0..width).contains(&cell.x)
+                    || !(0..height).contains(&cell.y)
+                    || walls.contains(&cell)
+            });
+
+            let cell = self.hazards[i].cell;
+            let Some(index) = self.snake.iter().position(|&segment| segment == cell) else {
+                continue;
+            };
+
+            if index == 0 {
+                return HazardOutcome::Died;
+            }
+
+            let new_len = index.max(2);
+            let cut = self.snake.len().saturating_sub(new_len);
+            self.snake.truncate(new_len);
+
+            if cut > 0 {
+                outcome = match outcome {
+                    HazardOutcome::CutTail(segments) => HazardOutcome::CutTail(segments + cut),
+                    _ => HazardOutcome::CutTail(cut),
+                };
+            }
+        }
+
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A snake heading right along the bottom row of a 5x5 board, head at
+    /// `(2, 0)`
+    fn straight_board() -> Board {
+        Board::new(
+            5,
+            5,
+            [GridPos::new(2, 0), GridPos::new(1, 0), GridPos::new(0, 0)],
+        )
+    }
+
+    fn snake(board: &Board) -> Vec<GridPos> {
+        board.snake.iter().copied().collect()
+    }
+
+    #[test]
+    fn step_moves_the_head_and_the_tail_after_it() {
+        let mut board = straight_board();
+
+        assert_eq!(board.step(Direction::Right), StepOutcome::Moved);
+        assert_eq!(
+            snake(&board),
+            [GridPos::new(3, 0), GridPos::new(2, 0), GridPos::new(1, 0)]
+        );
+    }
+
+    #[test]
+    fn step_out_of_bounds_or_into_a_wall_dies() {
+        let mut board = straight_board();
+        assert_eq!(
+            board.step(Direction::Down),
+            StepOutcome::Died(DeathCause::Wall)
+        );
+
+        let mut board = straight_board();
+        board.walls.insert(GridPos::new(3, 0));
+        assert_eq!(
+            board.step(Direction::Right),
+            StepOutcome::Died(DeathCause::Wall)
+        );
+
+        // A dead snake is left untouched
+        assert_eq!(
+            snake(&board),
+            [GridPos::new(2, 0), GridPos::new(1, 0), GridPos::new(0, 0)]
+        );
+    }
+
+    #[test]
+    fn step_out_of_each_edge_wraps_to_the_opposite_one() {
+        let cases = [
+            (GridPos::new(4, 2), Direction::Right, GridPos::new(0, 2)),
+            (GridPos::new(0, 2), Direction::Left, GridPos::new(4, 2)),
+            (GridPos::new(2, 4), Direction::Up, GridPos::new(2, 0)),
+            (GridPos::new(2, 0), Direction::Down, GridPos::new(2, 4)),
+        ];
+
+        for (head, direction, wrapped_head) in cases {
+            let neck = head + direction.opposite().delta();
+            let mut board = Board::new(5, 5, [head, neck]).with_wrap(true);
+
+            assert_eq!(board.step(direction), StepOutcome::Moved);
+            assert_eq!(snake(&board), [wrapped_head, head]);
+        }
+    }
+
+    #[test]
+    fn step_through_the_wrap_seam_pulls_the_tail_after_the_head() {
+        let mut board = Board::new(
+            5,
+            5,
+            [GridPos::new(4, 1), GridPos::new(3, 1), GridPos::new(2, 1)],
+        )
+        .with_wrap(true);
+
+        board.step(Direction::Right);
+        board.step(Direction::Right);
+        assert_eq!(
+            snake(&board),
+            [GridPos::new(1, 1), GridPos::new(0, 1), GridPos::new(4, 1)]
+        );
+
+        board.step(Direction::Right);
+        assert_eq!(
+            snake(&board),
+            [GridPos::new(2, 1), GridPos::new(1, 1), GridPos::new(0, 1)]
+        );
+    }
+
+    #[test]
+    fn step_through_the_wrap_seam_into_the_tail_dies() {
+        let mut board = Board::new(
+            5,
+            5,
+            [
+                GridPos::new(4, 1),
+                GridPos::new(4, 2),
+                GridPos::new(3, 2),
+                GridPos::new(2, 2),
+                GridPos::new(1, 2),
+                GridPos::new(0, 2),
+                GridPos::new(0, 1),
+                GridPos::new(0, 0),
+            ],
+        )
+        .with_wrap(true);
+
+        assert_eq!(
+            board.step(Direction::Right),
+            StepOutcome::Died(DeathCause::Tail)
+        );
+    }
+
+    #[test]
+    fn step_into_the_tail_dies() {
+        let mut board = Board::new(
+            5,
+            5,
+            [
+                GridPos::new(1, 1),
+                GridPos::new(2, 1),
+                GridPos::new(2, 0),
+                GridPos::new(1, 0),
+                GridPos::new(0, 0),
+            ],
+        );
+
+        assert_eq!(
+            board.step(Direction::Down),
+            StepOutcome::Died(DeathCause::Tail)
+        );
+        assert_eq!(board.tail().count(), 4);
+    }
+
+    #[test]
+    fn step_onto_food_eats_and_grows() {
+        let mut board = straight_board();
+        board.food.insert(GridPos::new(3, 0), FoodKind::Apple);
+
+        assert_eq!(
+            board.step(Direction::Right),
+            StepOutcome::Ate(FoodKind::Apple)
+        );
+        assert_eq!(
+            snake(&board),
+            [
+                GridPos::new(3, 0),
+                GridPos::new(2, 0),
+                GridPos::new(1, 0),
+                GridPos::new(0, 0)
+            ]
+        );
+        assert!(board.food.is_empty());
+    }
+
+    #[test]
+    fn step_onto_rotten_food_shrinks_down_to_a_head_and_a_tail() {
+        let row = |length: i32| (0..length).rev().map(|x| GridPos::new(x, 0));
+
+        for (length, shrunk_length) in [(6, 4), (4, 2), (3, 2), (2, 2)] {
+            let mut board = Board::new(8, 5, row(length));
+            board.food.insert(GridPos::new(length, 0), FoodKind::Rotten);
+
+            assert_eq!(
+                board.step(Direction::Right),
+                StepOutcome::Ate(FoodKind::Rotten)
+            );
+            assert_eq!(board.head(), GridPos::new(length, 0));
+            assert_eq!(board.snake.len(), shrunk_length);
+        }
+    }
+
+    /// `straight_board` with a portal in front of the head, paired with
+    /// `(1, 3)`
+    fn portal_board() -> Board {
+        let mut board = straight_board();
+        board.portals = HashMap::from([
+            (GridPos::new(3, 0), GridPos::new(1, 3)),
+            (GridPos::new(1, 3), GridPos::new(3, 0)),
+        ]);
+        board
+    }
+
+    #[test]
+    fn step_into_a_portal_comes_out_of_its_pair_with_the_same_heading() {
+        let mut board = portal_board();
+
+        assert_eq!(board.step(Direction::Right), StepOutcome::Moved);
+        assert_eq!(
+            snake(&board),
+            [GridPos::new(1, 3), GridPos::new(2, 0), GridPos::new(1, 0)]
+        );
+
+        // The tail follows through the portal and never covers its entry
+        board.step(Direction::Right);
+        board.step(Direction::Right);
+        assert_eq!(
+            snake(&board),
+            [GridPos::new(3, 3), GridPos::new(2, 3), GridPos::new(1, 3)]
+        );
+    }
+
+    #[test]
+    fn step_into_a_portal_whose_exit_the_tail_covers_dies() {
+        let mut board = portal_board();
+        board.snake = VecDeque::from([
+            GridPos::new(2, 0),
+            GridPos::new(2, 1),
+            GridPos::new(2, 2),
+            GridPos::new(2, 3),
+            GridPos::new(1, 3),
+            GridPos::new(0, 3),
+        ]);
+
+        assert_eq!(
+            board.step(Direction::Right),
+            StepOutcome::Died(DeathCause::Tail)
+        );
+    }
+
+    /// A snake of five along the bottom row and an enemy about to step
+    /// down onto its segment at `column`
+    fn hazard_board(column: i32) -> Board {
+        let mut board = Board::new(5, 5, (0..5).rev().map(|x| GridPos::new(x, 0)));
+        board.hazards = vec![Hazard::patrol(vec![
+            GridPos::new(column, 1),
+            GridPos::new(column, 0),
+        ])];
+        board
+    }
+
+    #[test]
+    fn step_hazards_onto_the_head_kills() {
+        let mut board = hazard_board(4);
+
+        assert_eq!(board.step_hazards(), HazardOutcome::Died);
+    }
+
+    #[test]
+    fn step_hazards_onto_the_tail_cuts_it_there() {
+        let mut board = hazard_board(1);
+
+        assert_eq!(board.step_hazards(), HazardOutcome::CutTail(2));
+        assert_eq!(
+            snake(&board),
+            [GridPos::new(4, 0), GridPos::new(3, 0), GridPos::new(2, 0)]
+        );
+    }
+
+    #[test]
+    fn step_hazards_onto_the_neck_keeps_a_tail_segment() {
+        let mut board = hazard_board(3);
+
+        assert_eq!(board.step_hazards(), HazardOutcome::CutTail(3));
+        assert_eq!(snake(&board), [GridPos::new(4, 0), GridPos::new(3, 0)]);
+    }
+
+    #[test]
+    fn step_into_an_enemy_dies() {
+        let mut board = straight_board();
+        board.hazards = vec![Hazard::bounce(GridPos::new(3, 0), Direction::Up)];
+
+        assert_eq!(
+            board.step(Direction::Right),
+            StepOutcome::Died(DeathCause::Hazard)
+        );
+    }
+
+    #[test]
+    fn step_onto_the_cell_the_tail_tip_leaves_moves() {
+        // A 2x2 loop, the head chasing the tail tip
+        let mut board = Board::new(
+            5,
+            5,
+            [
+                GridPos::new(0, 1),
+                GridPos::new(1, 1),
+                GridPos::new(1, 0),
+                GridPos::new(0, 0),
+            ],
+        );
+
+        assert_eq!(board.step(Direction::Down), StepOutcome::Moved);
+        assert_eq!(board.head(), GridPos::new(0, 0));
+        assert_eq!(board.tail().last(), Some(GridPos::new(1, 0)));
+    }
+}
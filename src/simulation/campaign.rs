@@ -0,0 +1,81 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// What the player has to do to clear a campaign level
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum LevelGoal {
+    Apples(usize),
+    SurviveSeconds(f32),
+}
+
+/// Ordered levels of the campaign, as written in a RON file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CampaignFile {
+    pub levels: Vec<CampaignEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CampaignEntry {
+    pub name: String,
+    // Level file, relative to the assets folder
+    pub path: String,
+    pub goal: LevelGoal,
+}
+
+/// Campaign progress kept between runs
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CampaignSave {
+    pub unlocked: usize,
+}
+
+#[derive(Debug)]
+pub enum CampaignError {
+    Parse(ron::error::SpannedError),
+    Invalid(String),
+}
+
+impl LevelGoal {
+    pub fn is_met(self, apples: usize, seconds: f32) -> bool {
+        match self {
+            LevelGoal::Apples(goal) => apples >= goal,
+            LevelGoal::SurviveSeconds(goal) => seconds >= goal,
+        }
+    }
+}
+
+impl CampaignFile {
+    pub fn from_ron(text: &str) -> Result<Self, CampaignError> {
+        let campaign: CampaignFile = ron::from_str(text).map_err(CampaignError::Parse)?;
+
+        if campaign.levels.is_empty() {
+            return Err(CampaignError::Invalid("the campaign has no levels".into()));
+        }
+
+        Ok(campaign)
+    }
+}
+
+impl fmt::Display for LevelGoal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelGoal::Apples(1) => write!(f, "Eat 1 apple"),
+            LevelGoal::Apples(apples) => write!(f, "Eat {apples} apples"),
+            LevelGoal::SurviveSeconds(seconds) => write!(f, "Survive {seconds} seconds"),
+        }
+    }
+}
+
+impl fmt::Display for CampaignError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CampaignError::Parse(err) => write!(f, "could not parse the campaign: {err}"),
+            CampaignError::Invalid(message) => write!(f, "invalid campaign: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for CampaignError {}
@@ -0,0 +1,113 @@
+use serde::Deserialize;
+
+/// Preset of how fast the snake starts and speeds up
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Insane,
+}
+
+/// Snake speed as a function of the apples eaten. The speed goes up one
+/// level every `apples_per_level` apples until `max_level`.
+#[derive(Clone, Copy, Debug)]
+pub struct SpeedCurve {
+    // Movements per second at level 1
+    pub start_rate: f64,
+    pub rate_per_level: f64,
+    pub apples_per_level: usize,
+    pub max_level: usize,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Insane,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Insane => "Insane",
+        }
+    }
+
+    pub fn speed_curve(self) -> SpeedCurve {
+        let (start_rate, rate_per_level, apples_per_level, max_level) = match self {
+            Difficulty::Easy => (4.0, 0.5, 5, 8),
+            Difficulty::Normal => (5.0, 1.0, 4, 10),
+            Difficulty::Hard => (7.0, 1.0, 3, 12),
+            Difficulty::Insane => (10.0, 1.5, 2, 15),
+        };
+
+        SpeedCurve {
+            start_rate,
+            rate_per_level,
+            apples_per_level,
+            max_level,
+        }
+    }
+}
+
+impl SpeedCurve {
+    /// Speed level, starting at 1, after eating `apples`
+    pub fn level(&self, apples: usize) -> usize {
+        (1 + apples / self.apples_per_level).min(self.max_level)
+    }
+
+    /// Movements per second at `level`
+    pub fn rate(&self, level: usize) -> f64 {
+        self.start_rate + (level - 1) as f64 * self.rate_per_level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_name_ignores_case() {
+        assert_eq!(Difficulty::from_name("hard"), Some(Difficulty::Hard));
+        assert_eq!(Difficulty::from_name("INSANE"), Some(Difficulty::Insane));
+        assert_eq!(Difficulty::from_name("medium"), None);
+    }
+
+    #[test]
+    fn level_goes_up_every_few_apples_until_the_max() {
+        let curve = Difficulty::Normal.speed_curve();
+
+        assert_eq!(curve.level(0), 1);
+        assert_eq!(curve.level(curve.apples_per_level - 1), 1);
+        assert_eq!(curve.level(curve.apples_per_level), 2);
+        assert_eq!(curve.level(usize::MAX), curve.max_level);
+    }
+
+    #[test]
+    fn rate_starts_at_the_start_rate_and_grows_per_level() {
+        let curve = Difficulty::Easy.speed_curve();
+
+        assert_eq!(curve.rate(1), curve.start_rate);
+        assert_eq!(curve.rate(3), curve.start_rate + 2.0 * curve.rate_per_level);
+    }
+
+    #[test]
+    fn harder_difficulties_are_faster_at_every_level() {
+        for pair in Difficulty::ALL.windows(2) {
+            let (easier, harder) = (pair[0].speed_curve(), pair[1].speed_curve());
+
+            assert!(harder.start_rate > easier.start_rate);
+            assert!(harder.rate(harder.max_level) > easier.rate(easier.max_level));
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum FoodKind {
+    Apple,
+    // Worth more points
+    Golden,
+    // Shrinks the tail
+    Rotten,
+    // Speeds the snake up for a while
+    Pepper,
+    // Slows the snake down for a while
+    Ice,
+}
+
+impl FoodKind {
+    pub const ALL: [FoodKind; 5] = [
+        FoodKind::Apple,
+        FoodKind::Golden,
+        FoodKind::Rotten,
+        FoodKind::Pepper,
+        FoodKind::Ice,
+    ];
+
+    pub fn points(self) -> usize {
+        match self {
+            FoodKind::Golden => 5,
+            FoodKind::Rotten => 0,
+            FoodKind::Apple | FoodKind::Pepper | FoodKind::Ice => 1,
+        }
+    }
+
+    /// Segments the snake gains, or loses when negative
+    pub fn growth(self) -> i32 {
+        match self {
+            FoodKind::Rotten => -2,
+            _ => 1,
+        }
+    }
+
+    /// Factor applied to the snake speed for a while
+    pub fn speed_factor(self) -> Option<f64> {
+        match self {
+            FoodKind::Pepper => Some(1.5),
+            FoodKind::Ice => Some(0.6),
+            _ => None,
+        }
+    }
+}
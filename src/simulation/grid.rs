@@ -0,0 +1,69 @@
+use std::ops::Add;
+
+use serde::{Deserialize, Serialize};
+
+/// A cell of the arena, counted from the bottom-left corner
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GridPos {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl GridPos {
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+}
+
+impl Add for GridPos {
+    type Output = GridPos;
+
+    fn add(self, other: GridPos) -> GridPos {
+        GridPos::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+/// Heading of the snake on the grid
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    /// Quarter turn counterclockwise
+    pub fn turn_left(self) -> Self {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    /// Quarter turn clockwise
+    pub fn turn_right(self) -> Self {
+        self.turn_left().opposite()
+    }
+
+    /// Offset to the next cell in this direction
+    pub fn delta(self) -> GridPos {
+        match self {
+            Direction::Up => GridPos::new(0, 1),
+            Direction::Down => GridPos::new(0, -1),
+            Direction::Left => GridPos::new(-1, 0),
+            Direction::Right => GridPos::new(1, 0),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use super::grid::{Direction, GridPos};
+
+/// How an enemy moves on its own tick
+#[derive(Clone, Debug)]
+pub enum HazardPath {
+    // Walks straight from waypoint to waypoint, back to the first after the
+    // last one
+    Patrol {
+        waypoints: Vec<GridPos>,
+        next: usize,
+    },
+    // Goes straight and turns around in front of walls
+    Bounce(Direction),
+}
+
+/// An enemy moving across the arena
+#[derive(Clone, Debug)]
+pub struct Hazard {
+    pub cell: GridPos,
+    pub path: HazardPath,
+}
+
+impl Hazard {
+    /// An enemy starting on the first waypoint
+    pub fn patrol(waypoints: Vec<GridPos>) -> Self {
+        Self {
+            cell: waypoints[0],
+            path: HazardPath::Patrol {
+                next: 1 % waypoints.len(),
+                waypoints,
+            },
+        }
+    }
+
+    pub fn bounce(cell: GridPos, direction: Direction) -> Self {
+        Self {
+            cell,
+            path: HazardPath::Bounce(direction),
+        }
+    }
+
+    /// Every cell a patrol walks through, or the start cell of a bouncing
+    /// enemy
+    pub fn route_cells(&self) -> Vec<GridPos> {
+        let HazardPath::Patrol { waypoints, .. } = &self.path else {
+            return vec![self.cell];
+        };
+
+        let mut cells = Vec::new();
+        for (i, &from) in waypoints.iter().enumerate() {
+            let to = waypoints[(i + 1) % waypoints.len()];
+            let mut cell = from;
+
+            cells.push(cell);
+            while cell != to {
+                cell = cell + step_towards(cell, to);
+                cells.push(cell);
+            }
+        }
+
+        cells
+    }
+
+    /// Moves the enemy one cell. `is_blocked` tells the cells a bouncing
+    /// enemy turns around in front of.
+    pub fn advance(&mut self, is_blocked: impl Fn(GridPos) -> bool) {
+        match &mut self.path {
+            HazardPath::Patrol { waypoints, next } => {
+                self.cell = self.cell + step_towards(self.cell, waypoints[*next]);
+
+                if self.cell == waypoints[*next] {
+                    *next = (*next + 1) % waypoints.len();
+                }
+            }
+            HazardPath::Bounce(direction) => {
+                if is_blocked(self.cell + direction.delta()) {
+                    *direction = direction.opposite();
+                }
+
+                let next_cell = self.cell + direction.delta();
+                if !is_blocked(next_cell) {
+                    self.cell = next_cell;
+                }
+            }
+        }
+    }
+}
+
+/// One step from `from` towards `to`, along a row or a column
+fn step_towards(from: GridPos, to: GridPos) -> GridPos {
+    GridPos::new((to.x - from.x).signum(), (to.y - from.y).signum())
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+/// Scores kept in each table
+pub const HIGH_SCORE_COUNT: usize = 5;
+
+/// Best scores of each game mode, highest first, kept between runs
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HighScores {
+    pub endless: Vec<usize>,
+    pub campaign: Vec<usize>,
+    pub time_attack: Vec<usize>,
+}
+
+/// Adds `score` to a table of best scores when it beats one of them or the
+/// table is not full. Returns whether it was added.
+pub fn record(table: &mut Vec<usize>, score: usize) -> bool {
+    let rank = table.partition_point(|&best| best >= score);
+
+    if score == 0 || rank >= HIGH_SCORE_COUNT {
+        return false;
+    }
+
+    table.insert(rank, score);
+    table.truncate(HIGH_SCORE_COUNT);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_keeps_the_best_scores_highest_first() {
+        let mut table = Vec::new();
+
+        for score in [3, 9, 5] {
+            assert!(record(&mut table, score));
+        }
+        assert_eq!(table, [9, 5, 3]);
+
+        // A tie ranks below the score it equals
+        assert!(record(&mut table, 5));
+        assert_eq!(table, [9, 5, 5, 3]);
+    }
+
+    #[test]
+    fn record_drops_scores_past_the_last_rank() {
+        let mut table = vec![50, 40, 30, 20, 10];
+
+        assert!(!record(&mut table, 10));
+        assert!(!record(&mut table, 5));
+        assert!(record(&mut table, 35));
+        assert_eq!(table, [50, 40, 35, 30, 20]);
+    }
+
+    #[test]
+    fn record_ignores_a_zero_score() {
+        let mut table = Vec::new();
+
+        assert!(!record(&mut table, 0));
+        assert!(table.is_empty());
+    }
+}
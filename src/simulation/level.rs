@@ -0,0 +1,439 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::grid::{Direction, GridPos};
+use super::hazard::{Hazard, HazardPath};
+
+pub const FREE_TILE: char = '.';
+pub const WALL_TILE: char = '#';
+pub const SNAKE_HEAD_TILE: char = 'H';
+pub const APPLE_TILE: char = 'A';
+// Each digit marks the two cells of a portal pair
+pub const PORTAL_TILES: std::ops::RangeInclusive<char> = '1'..='9';
+// Enemies bouncing between walls, starting towards the arrow
+pub const BOUNCE_UP_TILE: char = '^';
+pub const BOUNCE_DOWN_TILE: char = 'v';
+pub const BOUNCE_LEFT_TILE: char = '<';
+pub const BOUNCE_RIGHT_TILE: char = '>';
+
+/// Layout of an arena: its size, the interior walls and where the snake and
+/// the apples start
+#[derive(Clone, Debug)]
+pub struct Level {
+    pub width: i32,
+    pub height: i32,
+    pub walls: HashSet<GridPos>,
+    pub snake_start: GridPos,
+    pub snake_length: usize,
+    pub snake_direction: Direction,
+    // Cells the apple visits in order, random free cells when empty
+    pub apples: Vec<GridPos>,
+    // Linked cells, entering one comes out of the other
+    pub portals: Vec<(GridPos, GridPos)>,
+    pub hazards: Vec<Hazard>,
+}
+
+/// A level as written in a RON file. The `map` rows go from top to bottom
+/// and use the `*_TILE` characters. Each patrol lists the `(column, row)`
+/// map tiles an enemy walks between, counted from the top left tile.
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct LevelFile {
+    snake_length: usize,
+    snake_direction: Direction,
+    map: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    patrols: Vec<Vec<(i32, i32)>>,
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Parse(ron::error::SpannedError),
+    Invalid(String),
+    Write(ron::Error),
+}
+
+impl Level {
+    /// An arena without interior walls
+    pub fn empty(
+        width: i32,
+        height: i32,
+        snake_start: GridPos,
+        snake_length: usize,
+        snake_direction: Direction,
+    ) -> Self {
+        Self {
+            width,
+            height,
+            walls: HashSet::new(),
+            snake_start,
+            snake_length,
+            snake_direction,
+            apples: Vec::new(),
+            portals: Vec::new(),
+            hazards: Vec::new(),
+        }
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, LevelError> {
+        let file: LevelFile = ron::from_str(text).map_err(LevelError::Parse)?;
+        let invalid = |message: String| Err(LevelError::Invalid(message));
+
+        let height = file.map.len() as i32;
+        let width = file.map.first().map_or(0, |row| row.chars().count()) as i32;
+
+        let mut walls = HashSet::new();
+        let mut snake_start = None;
+        let mut apples = Vec::new();
+        let mut portal_cells: BTreeMap<char, Vec<GridPos>> = BTreeMap::new();
+        let mut hazards = Vec::new();
+
+        for (row_index, row) in file.map.iter().enumerate() {
+            if row.chars().count() as i32 != width {
+                return invalid(format!(
+                    "map row {} is not {width} tiles wide",
+                    row_index + 1
+                ));
+            }
+
+            let y = height - 1 - row_index as i32;
+
+            for (x, tile) in row.chars().enumerate() {
+                let cell = GridPos::new(x as i32, y);
+
+                match tile {
+                    FREE_TILE => {}
+                    WALL_TILE => {
+                        walls.insert(cell);
+                    }
+                    SNAKE_HEAD_TILE if snake_start.is_none() => snake_start = Some(cell),
+                    SNAKE_HEAD_TILE => return invalid("the map has several snake heads".into()),
+                    APPLE_TILE => apples.push(cell),
+                    tile if PORTAL_TILES.contains(&tile) => {
+                        portal_cells.entry(tile).or_default().push(cell)
+                    }
+                    BOUNCE_UP_TILE => hazards.push(Hazard::bounce(cell, Direction::Up)),
+                    BOUNCE_DOWN_TILE => hazards.push(Hazard::bounce(cell, Direction::Down)),
+                    BOUNCE_LEFT_TILE => hazards.push(Hazard::bounce(cell, Direction::Left)),
+                    BOUNCE_RIGHT_TILE => hazards.push(Hazard::bounce(cell, Direction::Right)),
+                    _ => {
+                        return invalid(format!(
+                            "unknown tile '{tile}' on map row {}",
+                            row_index + 1
+                        ))
+                    }
+                }
+            }
+        }
+
+        let Some(snake_start) = snake_start else {
+            return invalid(format!("the map has no snake head '{SNAKE_HEAD_TILE}'"));
+        };
+
+        let mut portals = Vec::new();
+        for (tile, cells) in portal_cells {
+            let [entry, exit] = cells[..] else {
+                return invalid(format!(
+                    "portal '{tile}' must be on exactly two cells, not {}",
+                    cells.len()
+                ));
+            };
+            portals.push((entry, exit));
+        }
+
+        for (patrol_index, tiles) in file.patrols.iter().enumerate() {
+            if tiles.is_empty() {
+                return invalid(format!("patrol {} has no tiles", patrol_index + 1));
+            }
+
+            let waypoints = tiles
+                .iter()
+                .map(|&(column, row)| GridPos::new(column, height - 1 - row))
+                .collect();
+            hazards.push(Hazard::patrol(waypoints));
+        }
+
+        let level = Level {
+            width,
+            height,
+            walls,
+            snake_start,
+            snake_length: file.snake_length,
+            snake_direction: file.snake_direction,
+            apples,
+            portals,
+            hazards,
+        };
+
+        level.validate()?;
+        Ok(level)
+    }
+
+    /// Writes the level in the format read by `from_ron`. Portal pairs get
+    /// their digits in order, so a level has at most nine of them.
+    pub fn to_ron(&self) -> Result<String, LevelError> {
+        self.validate()?;
+
+        if self.portals.len() > PORTAL_TILES.count() {
+            return Err(LevelError::Invalid(format!(
+                "a level has at most {} portal pairs",
+                PORTAL_TILES.count()
+            )));
+        }
+
+        let mut map = vec![vec![FREE_TILE; self.width as usize]; self.height as usize];
+        let mut set_tile = |cell: GridPos, tile: char| {
+            map[(self.height - 1 - cell.y) as usize][cell.x as usize] = tile;
+        };
+
+        for &cell in &self.walls {
+            set_tile(cell, WALL_TILE);
+        }
+        for &cell in &self.apples {
+            set_tile(cell, APPLE_TILE);
+        }
+        for (&(entry, exit), tile) in self.portals.iter().zip(PORTAL_TILES) {
+            set_tile(entry, tile);
+            set_tile(exit, tile);
+        }
+
+        let mut patrols = Vec::new();
+        for hazard in &self.hazards {
+            match &hazard.path {
+                HazardPath::Patrol { waypoints, .. } => patrols.push(
+                    waypoints
+                        .iter()
+                        .map(|cell| (cell.x, self.height - 1 - cell.y))
+                        .collect(),
+                ),
+                HazardPath::Bounce(direction) => {
+                    let tile = match direction {
+                        Direction::Up => BOUNCE_UP_TILE,
+                        Direction::Down => BOUNCE_DOWN_TILE,
+                        Direction::Left => BOUNCE_LEFT_TILE,
+                        Direction::Right => BOUNCE_RIGHT_TILE,
+                    };
+                    set_tile(hazard.cell, tile);
+                }
+            }
+        }
+
+        set_tile(self.snake_start, SNAKE_HEAD_TILE);
+
+        let file = LevelFile {
+            snake_length: self.snake_length,
+            snake_direction: self.snake_direction,
+            map: map.into_iter().map(String::from_iter).collect(),
+            patrols,
+        };
+
+        let pretty = ron::ser::PrettyConfig::new().depth_limit(2);
+        ron::ser::to_string_pretty(&file, pretty).map_err(LevelError::Write)
+    }
+
+    /// Removes the wall, apple, portal pair or enemy on `cell`. Returns
+    /// whether there was any.
+    pub fn clear_cell(&mut self, cell: GridPos) -> bool {
+        let count = self.walls.len() + self.apples.len() + self.portals.len() + self.hazards.len();
+
+        self.walls.remove(&cell);
+        self.apples.retain(|&apple| apple != cell);
+        self.portals
+            .retain(|&(entry, exit)| entry != cell && exit != cell);
+        self.hazards.retain(|hazard| hazard.cell != cell);
+
+        count != self.walls.len() + self.apples.len() + self.portals.len() + self.hazards.len()
+    }
+
+    pub fn validate(&self) -> Result<(), LevelError> {
+        let invalid = |message: String| Err(LevelError::Invalid(message));
+
+        if self.width < 2 || self.height < 2 {
+            return invalid("the arena must be at least 2x2 cells".into());
+        }
+        if self.snake_length < 2 {
+            return invalid("the snake must start with at least a head and a tail".into());
+        }
+
+        let in_bounds =
+            |cell: GridPos| (0..self.width).contains(&cell.x) && (0..self.height).contains(&cell.y);
+
+        let on_portal = |cell: GridPos| {
+            self.portals
+                .iter()
+                .any(|&(entry, exit)| cell == entry || cell == exit)
+        };
+
+        for cell in self.snake_cells() {
+            if !in_bounds(cell) || self.walls.contains(&cell) || on_portal(cell) {
+                return invalid(format!(
+                    "the snake body at ({}, {}) is outside the arena or on a wall or a portal",
+                    cell.x, cell.y
+                ));
+            }
+        }
+
+        for hazard in &self.hazards {
+            if let HazardPath::Patrol { waypoints, .. } = &hazard.path {
+                let aligned = waypoints
+                    .iter()
+                    .zip(waypoints.iter().cycle().skip(1))
+                    .all(|(from, to)| from.x == to.x || from.y == to.y);
+
+                if !aligned {
+                    return invalid(
+                        "each patrol tile must share a row or a column with the next one".into(),
+                    );
+                }
+            }
+
+            for cell in hazard.route_cells() {
+                if !in_bounds(cell) || self.walls.contains(&cell) {
+                    return invalid(format!(
+                        "the enemy path through ({}, {}) is outside the arena or on a wall",
+                        cell.x, cell.y
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Cells of the snake at the start, head first. The tail trails behind
+    /// the head, opposite to the starting direction.
+    pub fn snake_cells(&self) -> Vec<GridPos> {
+        let step = self.snake_direction.opposite().delta();
+
+        std::iter::successors(Some(self.snake_start), |&cell| Some(cell + step))
+            .take(self.snake_length)
+            .collect()
+    }
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Parse(err) => write!(f, "could not parse the level: {err}"),
+            LevelError::Invalid(message) => write!(f, "invalid level: {message}"),
+            LevelError::Write(err) => write!(f, "could not write the level: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level_text(snake_length: usize, map: &[&str]) -> String {
+        let rows: Vec<String> = map.iter().map(|row| format!("{row:?}")).collect();
+
+        format!(
+            "(snake_length: {snake_length}, snake_direction: Right, map: [{}])",
+            rows.join(", ")
+        )
+    }
+
+    fn assert_invalid<T>(result: Result<T, LevelError>) {
+        assert!(matches!(result, Err(LevelError::Invalid(_))));
+    }
+
+    #[test]
+    fn from_ron_reads_the_map_from_the_top_row() {
+        let level = Level::from_ron(&level_text(2, &["#...", ".A..", ".H..", "...#"])).unwrap();
+
+        assert_eq!((level.width, level.height), (4, 4));
+        assert_eq!(
+            level.walls,
+            HashSet::from([GridPos::new(0, 3), GridPos::new(3, 0)])
+        );
+        assert_eq!(level.apples, [GridPos::new(1, 2)]);
+        assert_eq!(
+            level.snake_cells(),
+            [GridPos::new(1, 1), GridPos::new(0, 1)]
+        );
+    }
+
+    #[test]
+    fn from_ron_rejects_broken_maps() {
+        assert!(matches!(
+            Level::from_ron("(snake_length: 2)"),
+            Err(LevelError::Parse(_))
+        ));
+
+        // Rows of different widths
+        assert_invalid(Level::from_ron(&level_text(2, &["....", ".H.", "...."])));
+        // Unknown tile
+        assert_invalid(Level::from_ron(&level_text(2, &["..x.", ".H..", "...."])));
+        // No snake head, then two of them
+        assert_invalid(Level::from_ron(&level_text(2, &["....", "....", "...."])));
+        assert_invalid(Level::from_ron(&level_text(2, &["..H.", ".H..", "...."])));
+    }
+
+    #[test]
+    fn validate_rejects_a_snake_that_does_not_fit() {
+        // Tail out of the arena, then on a wall
+        assert_invalid(Level::from_ron(&level_text(3, &["....", ".H..", "...."])));
+        assert_invalid(Level::from_ron(&level_text(2, &["....", "#H..", "...."])));
+        // No tail
+        assert_invalid(Level::from_ron(&level_text(1, &["....", ".H..", "...."])));
+
+        // Arena too narrow
+        assert_invalid(Level::empty(1, 4, GridPos::new(0, 1), 2, Direction::Up).validate());
+    }
+
+    #[test]
+    fn to_ron_reads_back_as_the_same_level() {
+        let mut level = Level::empty(8, 6, GridPos::new(3, 2), 3, Direction::Right);
+        level.walls = HashSet::from([GridPos::new(0, 5), GridPos::new(7, 0)]);
+        level.apples = vec![GridPos::new(6, 4), GridPos::new(1, 1)];
+        level.portals = vec![
+            (GridPos::new(1, 4), GridPos::new(6, 1)),
+            (GridPos::new(4, 5), GridPos::new(4, 0)),
+        ];
+        level.hazards = vec![
+            Hazard::bounce(GridPos::new(5, 3), Direction::Left),
+            Hazard::patrol(vec![GridPos::new(1, 2), GridPos::new(1, 3)]),
+        ];
+
+        let text = level.to_ron().unwrap();
+        let read = Level::from_ron(&text).unwrap();
+
+        assert_eq!((read.width, read.height), (level.width, level.height));
+        assert_eq!(read.walls, level.walls);
+        assert_eq!(read.snake_cells(), level.snake_cells());
+        assert_eq!(read.snake_direction, level.snake_direction);
+        // Apples are read back in map order
+        assert_eq!(
+            read.apples.iter().collect::<HashSet<_>>(),
+            level.apples.iter().collect::<HashSet<_>>()
+        );
+        assert_eq!(read.portals, level.portals);
+        assert_eq!(
+            read.hazards
+                .iter()
+                .map(Hazard::route_cells)
+                .collect::<Vec<_>>(),
+            level
+                .hazards
+                .iter()
+                .map(Hazard::route_cells)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(read.to_ron().unwrap(), text);
+    }
+
+    #[test]
+    fn to_ron_rejects_more_portals_than_digits() {
+        let mut level = Level::empty(20, 4, GridPos::new(1, 0), 2, Direction::Right);
+        level.portals = (0..10)
+            .map(|x| (GridPos::new(x, 2), GridPos::new(x, 3)))
+            .collect();
+
+        assert_invalid(level.to_ron());
+    }
+}
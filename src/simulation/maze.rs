@@ -0,0 +1,270 @@
+use std::collections::{HashSet, VecDeque};
+
+use rand::Rng;
+use serde::Deserialize;
+
+use super::grid::{Direction, GridPos};
+use super::level::Level;
+
+/// Shape of the walls the generator lays out
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum MazeStyle {
+    // Small scattered blocks
+    Blocks,
+    // Rectangular rooms with doors
+    Rooms,
+    // Long straight walls
+    Corridors,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum MazeDensity {
+    Sparse,
+    Medium,
+    Dense,
+}
+
+impl MazeStyle {
+    pub const ALL: [MazeStyle; 3] = [MazeStyle::Blocks, MazeStyle::Rooms, MazeStyle::Corridors];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "blocks" => Some(MazeStyle::Blocks),
+            "rooms" => Some(MazeStyle::Rooms),
+            "corridors" => Some(MazeStyle::Corridors),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MazeStyle::Blocks => "Blocks",
+            MazeStyle::Rooms => "Rooms",
+            MazeStyle::Corridors => "Corridors",
+        }
+    }
+}
+
+impl MazeDensity {
+    pub const ALL: [MazeDensity; 3] =
+        [MazeDensity::Sparse, MazeDensity::Medium, MazeDensity::Dense];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "sparse" => Some(MazeDensity::Sparse),
+            "medium" => Some(MazeDensity::Medium),
+            "dense" => Some(MazeDensity::Dense),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MazeDensity::Sparse => "Sparse",
+            MazeDensity::Medium => "Medium",
+            MazeDensity::Dense => "Dense",
+        }
+    }
+
+    /// Share of the free cells turned into walls
+    fn wall_ratio(self) -> f32 {
+        match self {
+            MazeDensity::Sparse => 0.08,
+            MazeDensity::Medium => 0.15,
+            MazeDensity::Dense => 0.25,
+        }
+    }
+}
+
+/// Adds random walls to the free cells of `level`. Every free cell reachable
+/// from the snake head stays reachable, and the `safe_corridor` cells in front of
+/// the head stay free so the snake does not start facing a wall.
+pub fn generate(
+    level: &Level,
+    style: MazeStyle,
+    density: MazeDensity,
+    safe_corridor: usize,
+    rng: &mut impl Rng,
+) -> Level {
+    let mut level = level.clone();
+
+    let step = level.snake_direction.delta();
+    let corridor = std::iter::successors(Some(level.snake_start + step), |&cell| Some(cell + step))
+        .take(safe_corridor);
+    let reserved: HashSet<GridPos> = level
+        .snake_cells()
+        .into_iter()
+        .chain(corridor)
+        .chain(level.apples.iter().copied())
+        .chain(
+            level
+                .portals
+                .iter()
+                .flat_map(|&(entry, exit)| [entry, exit]),
+        )
+        .chain(level.hazards.iter().flat_map(|hazard| hazard.route_cells()))
+        .collect();
+
+    let mut reachable = reachable_cells(&level);
+    let target = (reachable as f32 * density.wall_ratio()).round() as usize;
+    let mut placed = 0;
+
+    // Shapes may be cut short or rejected, give up after enough attempts
+    for _ in 0..target * 4 {
+        if placed >= target {
+            break;
+        }
+
+        for cell in random_shape(&level, style, rng) {
+            if placed >= target {
+                break;
+            }
+            if !in_bounds(&level, cell) || reserved.contains(&cell) || level.walls.contains(&cell) {
+                continue;
+            }
+
+            level.walls.insert(cell);
+
+            // Only the new wall cell itself may leave the reachable area
+            let now_reachable = reachable_cells(&level);
+            if now_reachable + 1 == reachable {
+                reachable = now_reachable;
+                placed += 1;
+            } else {
+                level.walls.remove(&cell);
+            }
+        }
+    }
+
+    level
+}
+
+fn in_bounds(level: &Level, cell: GridPos) -> bool {
+    (0..level.width).contains(&cell.x) && (0..level.height).contains(&cell.y)
+}
+
+/// Cells of a wall shape at a random place. It may stick out of the arena.
+fn random_shape(level: &Level, style: MazeStyle, rng: &mut impl Rng) -> Vec<GridPos> {
+    let origin = GridPos::new(
+        rng.gen_range(0..level.width),
+        rng.gen_range(0..level.height),
+    );
+    let rectangle = |width: i32, height: i32| {
+        (0..height).flat_map(move |y| (0..width).map(move |x| origin + GridPos::new(x, y)))
+    };
+
+    match style {
+        MazeStyle::Blocks => rectangle(rng.gen_range(1..=2), rng.gen_range(1..=2)).collect(),
+        MazeStyle::Rooms => {
+            let width = rng.gen_range(4..=8);
+            let height = rng.gen_range(3..=6);
+            let mut outline: Vec<GridPos> = rectangle(width, height)
+                .filter(|cell| {
+                    cell.x == origin.x
+                        || cell.y == origin.y
+                        || cell.x == origin.x + width - 1
+                        || cell.y == origin.y + height - 1
+                })
+                .collect();
+
+            // The door, more may open where a wall would cut cells off
+            outline.remove(rng.gen_range(0..outline.len()));
+            outline
+        }
+        MazeStyle::Corridors => {
+            let direction = if rng.gen_bool(0.5) {
+                Direction::Right
+            } else {
+                Direction::Up
+            };
+            let length = rng.gen_range(2..=(level.width.max(level.height) / 2).max(2));
+
+            std::iter::successors(Some(origin), |&cell| Some(cell + direction.delta()))
+                .take(length as usize)
+                .collect()
+        }
+    }
+}
+
+/// Number of cells the snake head can reach without crossing a wall,
+/// going through portals
+fn reachable_cells(level: &Level) -> usize {
+    let mut visited = HashSet::from([level.snake_start]);
+    let mut queue = VecDeque::from([level.snake_start]);
+
+    while let Some(cell) = queue.pop_front() {
+        let portal_exits = level.portals.iter().filter_map(|&(entry, exit)| {
+            if cell == entry {
+                Some(exit)
+            } else if cell == exit {
+                Some(entry)
+            } else {
+                None
+            }
+        });
+        let neighbours = [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ]
+        .map(|direction| cell + direction.delta());
+
+        for next in neighbours.into_iter().chain(portal_exits) {
+            if in_bounds(level, next) && !level.walls.contains(&next) && visited.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    visited.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn arena() -> Level {
+        let mut level = Level::empty(20, 15, GridPos::new(4, 7), 3, Direction::Right);
+        level.apples = vec![GridPos::new(15, 3), GridPos::new(10, 12)];
+        level
+    }
+
+    #[test]
+    fn generate_keeps_every_free_cell_reachable() {
+        let arena = arena();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        for style in MazeStyle::ALL {
+            for density in MazeDensity::ALL {
+                for _ in 0..2 {
+                    let maze = generate(&arena, style, density, 3, &mut rng);
+                    let free_cells = (arena.width * arena.height) as usize - maze.walls.len();
+
+                    assert!(!maze.walls.is_empty());
+                    assert_eq!(reachable_cells(&maze), free_cells);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generate_leaves_the_snake_its_corridor_and_the_apples_free() {
+        let arena = arena();
+        let mut rng = StdRng::seed_from_u64(11);
+
+        for style in MazeStyle::ALL {
+            let maze = generate(&arena, style, MazeDensity::Dense, 3, &mut rng);
+            let corridor = (5..8).map(|x| GridPos::new(x, 7));
+
+            for cell in arena.snake_cells().into_iter().chain(corridor) {
+                assert!(!maze.walls.contains(&cell));
+            }
+            for cell in &arena.apples {
+                assert!(!maze.walls.contains(cell));
+            }
+        }
+    }
+}
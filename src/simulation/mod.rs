@@ -0,0 +1,9 @@
+pub mod board;
+pub mod campaign;
+pub mod difficulty;
+pub mod food;
+pub mod grid;
+pub mod hazard;
+pub mod high_scores;
+pub mod level;
+pub mod maze;
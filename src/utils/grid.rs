@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+
+use crate::data::config::GameConfig;
+use crate::simulation::grid::{Direction, GridPos};
+
+/// Where the grid of the current level sits in the world
+#[derive(Resource, Clone, Copy)]
+pub struct ArenaGeometry {
+    pub center: Vec2,
+    pub columns: i32,
+    pub rows: i32,
+    pub cell_size: f32,
+    pub wall_thickness: f32,
+}
+
+impl ArenaGeometry {
+    pub fn new(config: &GameConfig, columns: i32, rows: i32) -> Self {
+        Self {
+            center: config.arena_center(),
+            columns,
+            rows,
+            cell_size: config.snake.size,
+            wall_thickness: config.arena.wall_thickness,
+        }
+    }
+
+    /// Size of the space between the walls
+    pub fn inner_size(&self) -> Vec2 {
+        Vec2::new(self.columns as f32, self.rows as f32) * self.cell_size
+    }
+
+    /// World position of the center of `cell`
+    pub fn cell_to_translation(&self, cell: GridPos, z: f32) -> Vec3 {
+        let origin = self.center - (self.inner_size() - self.cell_size) / 2.0;
+
+        (origin + Vec2::new(cell.x as f32, cell.y as f32) * self.cell_size).extend(z)
+    }
+
+    /// Cell under a world position, `None` outside of the walls
+    pub fn translation_to_cell(&self, translation: Vec2) -> Option<GridPos> {
+        let origin = self.center - (self.inner_size() - self.cell_size) / 2.0;
+        let cell = ((translation - origin) / self.cell_size).round();
+        let cell = GridPos::new(cell.x as i32, cell.y as i32);
+
+        ((0..self.columns).contains(&cell.x) && (0..self.rows).contains(&cell.y)).then_some(cell)
+    }
+}
+
+impl Direction {
+    /// Rotation of a sprite drawn facing right
+    pub fn rotation(self) -> Quat {
+        let angle = match self {
+            Direction::Right => 0.0,
+            Direction::Up => std::f32::consts::PI / 2.0,
+            Direction::Left => std::f32::consts::PI,
+            Direction::Down => std::f32::consts::PI * 3.0 / 2.0,
+        };
+
+        Quat::from_rotation_z(angle)
+    }
+}
@@ -1 +1 @@
-
+pub mod grid;